@@ -0,0 +1,247 @@
+use std::{
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Result, anyhow};
+use clap::{
+    Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum, error::ErrorKind,
+    parser::ValueSource,
+};
+use serde::Serialize;
+
+use crate::{
+    LocalLoreContext,
+    collectors::gather_project_dependencies,
+    jobs::{ApplicationJob, submit_application_job},
+    tools,
+};
+
+pub const DEFAULT_HTTP_PORT: u16 = 7341;
+
+/// Indexes the dependencies of your projects and serves their documentation
+/// to LLM agents over MCP.
+#[derive(Debug, Parser)]
+#[command(name = "local-lore", version)]
+pub struct Cli {
+    /// Log to stderr, which the server always does.
+    #[arg(long, global = true)]
+    pub verbose: bool,
+
+    /// The config file, defaults to `local-lore/config.toml` in the user's
+    /// config directory.
+    #[arg(long, global = true, env = "LOCAL_LORE_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub serve: ServeArgs,
+}
+
+impl Cli {
+    /// Parses the process arguments, exiting on invalid ones.
+    pub fn parse_args() -> Self {
+        Self::try_parse_args(env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    /// The server's own arguments conflict with subcommands, so
+    /// `local-lore --transport http scan` is rejected rather than ignoring
+    /// them. Clap's `args_conflicts_with_subcommands` would reject the global
+    /// flags before a subcommand as well.
+    fn try_parse_args<I, T>(args: I) -> clap::error::Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = Self::command().try_get_matches_from(args)?;
+        if let Some((subcommand, _)) = matches.subcommand() {
+            let given = ["transport", "port"]
+                .into_iter()
+                .find(|id| matches.value_source(id) == Some(ValueSource::CommandLine));
+            if let Some(id) = given {
+                return Err(Self::command().error(
+                    ErrorKind::ArgumentConflict,
+                    format!(
+                        "the argument '--{}' cannot be used with '{}'",
+                        id, subcommand
+                    ),
+                ));
+            }
+        }
+        Self::from_arg_matches(&matches)
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the MCP server and the background jobs. This is the default.
+    Serve(ServeArgs),
+    /// Scan the dependencies of a project into the index.
+    Scan(ScanArgs),
+    /// List the dependencies of an indexed project.
+    Deps(DepsArgs),
+    /// Search the indexed dependency documentation.
+    Search(SearchArgs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Transport {
+    /// Serve the one editor or agent that started the process.
+    Stdio,
+    /// Serve any number of clients on a localhost port.
+    Http,
+}
+
+#[derive(Debug, Args)]
+pub struct ServeArgs {
+    #[arg(long, value_enum, env = "LOCAL_LORE_TRANSPORT", default_value_t = Transport::Stdio)]
+    pub transport: Transport,
+
+    /// The localhost port the HTTP transport listens on.
+    #[arg(long, env = "LOCAL_LORE_HTTP_PORT", default_value_t = DEFAULT_HTTP_PORT)]
+    pub port: u16,
+}
+
+#[derive(Debug, Args)]
+pub struct ScanArgs {
+    /// The project directory, defaults to the current one.
+    #[arg(default_value = ".")]
+    pub path: String,
+}
+
+#[derive(Debug, Args)]
+pub struct DepsArgs {
+    /// The project directory, defaults to the current one.
+    #[arg(default_value = ".")]
+    pub project: String,
+
+    /// Only dependencies whose name contains this.
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// One of rust, javascript, python or go.
+    #[arg(long)]
+    pub language: Option<String>,
+
+    /// One of normal, dev, build, optional or peer.
+    #[arg(long)]
+    pub kind: Option<String>,
+
+    /// Only the dependencies the project declares itself.
+    #[arg(long)]
+    pub direct: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    pub query: String,
+
+    /// Only the documentation of this project's dependencies.
+    #[arg(long)]
+    pub project: Option<String>,
+
+    #[arg(long)]
+    pub language: Option<String>,
+
+    /// Only the documentation of this dependency.
+    #[arg(long)]
+    pub name: Option<String>,
+
+    #[arg(long, requires = "name")]
+    pub version: Option<String>,
+
+    #[arg(long)]
+    pub limit: Option<u64>,
+}
+
+/// Scans the project right away. Indexing its documentation is left to the
+/// job queue, so it happens the next time the server is running.
+pub async fn scan(args: ScanArgs, ctx: &Arc<LocalLoreContext>) -> Result<()> {
+    let path = Path::new(&args.path)
+        .canonicalize()
+        .map_err(|e| anyhow!("Cannot access {}: {}", args.path, e))?;
+    let path = path
+        .to_str()
+        .ok_or_else(|| anyhow!("Project path contains invalid unicode"))?;
+
+    let summary = gather_project_dependencies(path, ctx).await?;
+    submit_application_job(ApplicationJob::IndexDependencyDocumentation, ctx).await?;
+    print_json(&summary)
+}
+
+pub async fn deps(args: DepsArgs, ctx: &Arc<LocalLoreContext>) -> Result<()> {
+    let input = tools::search_deps::SearchDepsInput {
+        name: args.name,
+        language: args.language,
+        project_path: Some(project_path(&args.project)),
+        kind: args.kind,
+        direct: args.direct.then_some(true),
+    };
+    print_json(&tools::search_deps::search_deps(input, ctx).await?)
+}
+
+pub async fn search(args: SearchArgs, ctx: &Arc<LocalLoreContext>) -> Result<()> {
+    let input = tools::search_docs::SearchDocsInput {
+        query: args.query,
+        project_path: args.project.as_deref().map(project_path),
+        language: args.language,
+        name: args.name,
+        version: args.version,
+        limit: args.limit,
+    };
+    print_json(&tools::search_docs::search_docs(input, ctx).await?)
+}
+
+/// Projects are stored under their canonical path, a project that no longer
+/// exists on disk is looked up as given.
+fn project_path(path: &str) -> String {
+    Path::new(path)
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.to_str().map(str::to_string))
+        .unwrap_or_else(|| path.to_string())
+}
+
+fn print_json(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> clap::error::Result<Cli> {
+        Cli::try_parse_args(["local-lore"].into_iter().chain(args.split_whitespace()))
+    }
+
+    #[test]
+    fn global_flags_go_before_or_after_the_subcommand() {
+        for args in [
+            "--verbose scan .",
+            "scan --verbose .",
+            "--config a.toml --verbose scan .",
+            "--config=a.toml scan --verbose .",
+        ] {
+            let cli = parse(args).unwrap();
+            assert!(cli.verbose, "{}", args);
+            assert!(matches!(cli.command, Some(Command::Scan(_))), "{}", args);
+        }
+        let cli = parse("--config a.toml deps --direct").unwrap();
+        assert_eq!(cli.config, Some(PathBuf::from("a.toml")));
+    }
+
+    #[test]
+    fn server_arguments_conflict_with_subcommands() {
+        assert!(parse("--transport http scan .").is_err());
+        assert!(parse("--verbose --port 1 scan .").is_err());
+
+        let cli = parse("--verbose --transport http").unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.serve.transport, Transport::Http);
+    }
+}
@@ -1,57 +1,344 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
-use cargo_metadata::{DependencyKind, MetadataCommand};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use cargo_metadata::{
+    CargoOpt, DependencyKind as CargoDependencyKind, MetadataCommand, Node, Package, PackageId,
+};
 use log::debug;
 
-use crate::{collectors::CollectorDependency, models::ProjectLanguage};
+use crate::{
+    collectors::{
+        Collector, CollectorDependency, CollectorOutput, WorkspaceMember, dedupe_dependencies,
+        inherit_kinds, member_dependencies,
+    },
+    models::{DependencyKind, ProjectLanguage},
+};
 
-pub async fn collect_dependencies(path: &str) -> Result<Vec<CollectorDependency>> {
+pub struct CargoLockCollector;
+
+#[async_trait]
+impl Collector for CargoLockCollector {
+    fn language(&self) -> ProjectLanguage {
+        ProjectLanguage::Rust
+    }
+
+    fn applies_to(&self, path: &Path) -> bool {
+        path.join("Cargo.toml").is_file()
+    }
+
+    fn watches(&self, file_name: &str) -> bool {
+        matches!(file_name, "Cargo.toml" | "Cargo.lock")
+    }
+
+    async fn collect(&self, path: &str) -> Result<CollectorOutput> {
+        collect_dependencies(path).await
+    }
+}
+
+pub async fn collect_dependencies(path: &str) -> Result<CollectorOutput> {
     debug!("Scanning directory: {}", path);
 
-    let metadata = match MetadataCommand::new()
+    // All features, so optional dependencies are part of the resolve graph.
+    // A manifest that cannot be read fails the scan rather than reporting no
+    // dependencies, so the scan is retried instead of pruning everything.
+    let metadata = MetadataCommand::new()
         .manifest_path(format!("{}/Cargo.toml", path))
+        .features(CargoOpt::AllFeatures)
         .exec()
-    {
-        Ok(metadata) => metadata,
-        Err(e) => {
-            debug!("Failed to execute cargo metadata for {}: {}", path, e);
-            return Ok(Vec::new());
-        }
-    };
+        .with_context(|| format!("Failed to execute cargo metadata for {}", path))?;
 
     debug!("Found {} packages in workspace", metadata.packages.len());
 
-    let mut dependencies_map: HashMap<(String, String), CollectorDependency> = HashMap::new();
+    let Some(resolve) = metadata.resolve.as_ref() else {
+        debug!("No resolve graph in cargo metadata for {}", path);
+        return Ok(CollectorOutput::default());
+    };
+    let graph = ResolveGraph {
+        packages: metadata.packages.iter().map(|p| (&p.id, p)).collect(),
+        nodes: resolve.nodes.iter().map(|n| (&n.id, n)).collect(),
+        workspace_members: metadata.workspace_members.iter().collect(),
+    };
+
+    debug!("Found {} workspace members", graph.workspace_members.len());
 
-    let workspace_members: Vec<_> = metadata
+    // cargo metadata reports the whole workspace even when run from inside a
+    // member, in which case only that member is the project.
+    let scanned_root = canonical(Path::new(path));
+    let members: Vec<(&PackageId, &Package)> = metadata
         .workspace_members
         .iter()
-        .filter_map(|member_id| metadata.packages.iter().find(|p| &p.id == member_id))
+        .filter_map(|id| graph.packages.get(id).map(|p| (id, *p)))
         .collect();
+    let scanned_members: Vec<&PackageId> =
+        if canonical(metadata.workspace_root.as_std_path()) == scanned_root {
+            members.iter().map(|(id, _)| *id).collect()
+        } else {
+            members
+                .iter()
+                .filter(|(_, p)| package_dir(p).is_some_and(|dir| canonical(dir) == scanned_root))
+                .map(|(id, _)| *id)
+                .collect()
+        };
+
+    let mut dependencies = dedupe_dependencies(graph.reachable_from(&scanned_members));
+    inherit_kinds(&mut dependencies);
+
+    debug!("Found {} unique dependencies", dependencies.len());
+
+    let workspace_members = if scanned_members.len() > 1 {
+        members
+            .iter()
+            .filter(|(id, _)| scanned_members.contains(id))
+            // A root package is the workspace project itself.
+            .filter(|(_, package)| {
+                package_dir(package).is_none_or(|dir| canonical(dir) != scanned_root)
+            })
+            .filter_map(|(id, package)| {
+                let mut member_dependencies =
+                    member_dependencies(&dependencies, &graph.direct_kinds(&[id]));
+                inherit_kinds(&mut member_dependencies);
+                Some(WorkspaceMember {
+                    name: package.name.to_string(),
+                    path: package_dir(package)?.to_str()?.to_string(),
+                    dependencies: member_dependencies,
+                })
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(CollectorOutput {
+        dependencies,
+        members: workspace_members,
+    })
+}
 
-    debug!("Found {} workspace members", workspace_members.len());
+struct ResolveGraph<'a> {
+    packages: HashMap<&'a PackageId, &'a Package>,
+    nodes: HashMap<&'a PackageId, &'a Node>,
+    workspace_members: HashSet<&'a PackageId>,
+}
 
-    for package in workspace_members {
-        for dep in &package.dependencies {
-            if matches!(dep.kind, DependencyKind::Build) {
+impl ResolveGraph<'_> {
+    /// The resolve node of each member names the exact package every one of
+    /// its dependencies resolved to, including renamed ones. The manifest is
+    /// only needed to tell which of them are optional.
+    fn direct_kinds(&self, members: &[&PackageId]) -> HashMap<(String, String), DependencyKind> {
+        let mut direct_kinds: HashMap<(String, String), DependencyKind> = HashMap::new();
+        for member_id in members {
+            let (Some(node), Some(member)) =
+                (self.nodes.get(member_id), self.packages.get(member_id))
+            else {
                 continue;
+            };
+            for node_dep in &node.deps {
+                if self.workspace_members.contains(&node_dep.pkg) {
+                    continue;
+                }
+                let Some(resolved_package) = self.packages.get(&node_dep.pkg) else {
+                    continue;
+                };
+                let declared = |kind: CargoDependencyKind| {
+                    member.dependencies.iter().find(|dep| {
+                        dep.kind == kind
+                            && dep.rename.as_deref().unwrap_or(&dep.name).replace('-', "_")
+                                == node_dep.name
+                            && *dep.name == *resolved_package.name
+                    })
+                };
+                let kinds = node_dep.dep_kinds.iter().map(|info| match info.kind {
+                    CargoDependencyKind::Development => DependencyKind::Dev,
+                    CargoDependencyKind::Build => DependencyKind::Build,
+                    kind if declared(kind).is_some_and(|dep| dep.optional) => {
+                        DependencyKind::Optional
+                    }
+                    _ => DependencyKind::Normal,
+                });
+                let kind = kinds
+                    .reduce(DependencyKind::strongest)
+                    .unwrap_or(DependencyKind::Normal);
+                direct_kinds
+                    .entry((
+                        resolved_package.name.to_string(),
+                        resolved_package.version.to_string(),
+                    ))
+                    .and_modify(|existing| *existing = existing.strongest(kind))
+                    .or_insert(kind);
             }
+        }
+        direct_kinds
+    }
 
-            if let Some(resolved_package) = metadata.packages.iter().find(|p| p.name == dep.name) {
-                let name = resolved_package.name.to_string();
-                let version = resolved_package.version.to_string();
-                let key = (name.clone(), version.clone());
-                dependencies_map.entry(key).or_insert_with(|| {
-                    CollectorDependency::new(name, version, ProjectLanguage::Rust)
-                });
+    /// Walks the resolve graph from the given members so transitive crates and
+    /// the edges between them are recorded too.
+    fn reachable_from(&self, members: &[&PackageId]) -> Vec<CollectorDependency> {
+        let direct_kinds = self.direct_kinds(members);
+        let mut queue: VecDeque<&PackageId> = members.iter().copied().collect();
+        let mut visited: HashSet<&PackageId> = self.workspace_members.clone();
+        let mut dependencies = Vec::new();
+        while let Some(id) = queue.pop_front() {
+            let Some(node) = self.nodes.get(id) else {
+                continue;
+            };
+            let children: Vec<&PackageId> = node
+                .deps
+                .iter()
+                .map(|d| &d.pkg)
+                .filter(|pkg| !self.workspace_members.contains(pkg))
+                .collect();
+            for child in &children {
+                if visited.insert(child) {
+                    queue.push_back(child);
+                }
             }
+            if self.workspace_members.contains(id) {
+                continue;
+            }
+            let Some(package) = self.packages.get(id) else {
+                continue;
+            };
+
+            let name = package.name.to_string();
+            let version = package.version.to_string();
+            let source_path = package_dir(package).and_then(|p| p.to_str().map(str::to_string));
+            let depends_on = children
+                .iter()
+                .filter_map(|child| self.packages.get(child))
+                .map(|child| (child.name.to_string(), child.version.to_string()))
+                .collect();
+            let (kind, is_direct) = match direct_kinds.get(&(name.clone(), version.clone())) {
+                Some(kind) => (*kind, true),
+                None => (DependencyKind::Normal, false),
+            };
+            dependencies.push(CollectorDependency::new(
+                name,
+                version,
+                ProjectLanguage::Rust,
+                source_path,
+                kind,
+                is_direct,
+                depends_on,
+            ));
         }
+        dependencies
     }
+}
 
-    let dependencies: Vec<CollectorDependency> = dependencies_map.into_values().collect();
+fn package_dir(package: &Package) -> Option<&Path> {
+    package.manifest_path.parent().map(|p| p.as_std_path())
+}
 
-    debug!("Found {} unique dependencies", dependencies.len());
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(dependencies)
+    const FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/cargo_multi_version"
+    );
+
+    fn find<'a>(
+        dependencies: &'a [CollectorDependency],
+        name: &str,
+        version: &str,
+    ) -> &'a CollectorDependency {
+        dependencies
+            .iter()
+            .find(|d| d.name == name && d.version == version)
+            .unwrap_or_else(|| panic!("{} {} was not collected", name, version))
+    }
+
+    #[tokio::test]
+    async fn records_every_resolved_version_of_a_crate() {
+        let dependencies = collect_dependencies(FIXTURE).await.unwrap().dependencies;
+
+        let mut shared: Vec<&str> = dependencies
+            .iter()
+            .filter(|d| d.name == "shared")
+            .map(|d| d.version.as_str())
+            .collect();
+        shared.sort();
+        assert_eq!(shared, ["1.0.0", "2.0.0"]);
+        assert!(
+            dependencies
+                .iter()
+                .all(|d| d.name != "app" && d.name != "helper")
+        );
+    }
+
+    #[tokio::test]
+    async fn direct_dependency_uses_the_version_it_resolved_to() {
+        let dependencies = collect_dependencies(FIXTURE).await.unwrap().dependencies;
+
+        let shared = find(&dependencies, "shared", "2.0.0");
+        assert!(shared.is_direct);
+        assert_eq!(shared.kind, DependencyKind::Normal);
+        assert!(
+            shared
+                .source_path
+                .as_deref()
+                .is_some_and(|p| p.ends_with("vendor/shared-2"))
+        );
+    }
+
+    #[tokio::test]
+    async fn renamed_dependency_resolves_to_the_renamed_package() {
+        let dependencies = collect_dependencies(FIXTURE).await.unwrap().dependencies;
+
+        // Only declared as `legacy = { package = "shared", optional = true }`.
+        let shared = find(&dependencies, "shared", "1.0.0");
+        assert!(shared.is_direct);
+        assert_eq!(shared.kind, DependencyKind::Optional);
+        assert!(
+            shared
+                .source_path
+                .as_deref()
+                .is_some_and(|p| p.ends_with("vendor/shared-1"))
+        );
+    }
+
+    #[tokio::test]
+    async fn edges_point_at_the_resolved_version() {
+        let dependencies = collect_dependencies(FIXTURE).await.unwrap().dependencies;
+
+        let wrapper = find(&dependencies, "wrapper", "0.3.0");
+        assert!(wrapper.is_direct);
+        assert_eq!(wrapper.kind, DependencyKind::Dev);
+        assert_eq!(
+            wrapper.depends_on,
+            [("shared".to_string(), "1.0.0".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn workspace_members_get_their_own_dependencies() {
+        let output = collect_dependencies(FIXTURE).await.unwrap();
+
+        let member = |name: &str| {
+            output
+                .members
+                .iter()
+                .find(|m| m.name == name)
+                .unwrap_or_else(|| panic!("{} was not reported as a member", name))
+        };
+        let helper = member("helper");
+        assert!(helper.path.ends_with("cargo_multi_version/helper"));
+        assert_eq!(helper.dependencies.len(), 1);
+        let shared = find(&helper.dependencies, "shared", "2.0.0");
+        assert!(shared.is_direct);
+        assert_eq!(shared.kind, DependencyKind::Normal);
+
+        let app = member("app");
+        assert_eq!(app.dependencies.len(), output.dependencies.len());
+        assert!(find(&app.dependencies, "wrapper", "0.3.0").is_direct);
+    }
 }
@@ -0,0 +1,390 @@
+use std::{collections::HashMap, fs::read_to_string, path::Path};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use log::debug;
+use semver::Version;
+
+use crate::{
+    collectors::{Collector, CollectorDependency, CollectorOutput},
+    models::{DependencyKind, ProjectLanguage},
+};
+
+#[derive(Debug, Default)]
+struct GoModFile {
+    requires: Vec<GoRequire>,
+    replaces: Vec<GoReplace>,
+}
+
+#[derive(Debug)]
+struct GoRequire {
+    module: String,
+    version: String,
+    /// Marked `// indirect`, i.e. needed by a dependency rather than imported by the module.
+    indirect: bool,
+}
+
+#[derive(Debug)]
+struct GoReplace {
+    module: String,
+    version: Option<String>,
+    target: GoReplaceTarget,
+}
+
+#[derive(Debug)]
+enum GoReplaceTarget {
+    Module(String, String),
+    LocalPath,
+}
+
+impl GoModFile {
+    /// The module and version that stand in for `module` at `version`, or
+    /// `None` when a local directory replaces it. A replacement of the exact
+    /// version wins over one of every version.
+    fn replacement(&self, module: &str, version: &str) -> Option<(String, String)> {
+        let replaces = || self.replaces.iter().filter(|r| r.module == module);
+        let replace = replaces()
+            .find(|r| r.version.as_deref() == Some(version))
+            .or_else(|| replaces().find(|r| r.version.is_none()));
+        match replace.map(|r| &r.target) {
+            Some(GoReplaceTarget::LocalPath) => None,
+            Some(GoReplaceTarget::Module(target, target_version)) => {
+                Some((target.clone(), target_version.clone()))
+            }
+            None => Some((module.to_string(), version.to_string())),
+        }
+    }
+}
+
+pub struct GoModCollector;
+
+#[async_trait]
+impl Collector for GoModCollector {
+    fn language(&self) -> ProjectLanguage {
+        ProjectLanguage::Go
+    }
+
+    fn applies_to(&self, path: &Path) -> bool {
+        path.join("go.mod").is_file()
+    }
+
+    fn watches(&self, file_name: &str) -> bool {
+        matches!(file_name, "go.mod" | "go.sum")
+    }
+
+    async fn collect(&self, path: &str) -> Result<CollectorOutput> {
+        collect_dependencies(path).await.map(CollectorOutput::from)
+    }
+}
+
+pub async fn collect_dependencies(path: &str) -> Result<Vec<CollectorDependency>> {
+    debug!("Scanning directory for go.mod: {}", path);
+
+    let go_mod_path = format!("{}/go.mod", path);
+    // Scanning with nothing would drop every link, a go.mod caught while it
+    // is rewritten fails the scan so it is retried instead.
+    let contents = read_to_string(&go_mod_path)
+        .with_context(|| format!("Failed to read go.mod for {}", path))?;
+
+    let go_mod = parse_go_mod(&contents);
+    debug!(
+        "Found {} requirements and {} replacements in go.mod",
+        go_mod.requires.len(),
+        go_mod.replaces.len()
+    );
+
+    // Maps each module to its version and whether the module imports it itself.
+    let mut resolved: HashMap<String, (String, bool)> = HashMap::new();
+    for require in &go_mod.requires {
+        match go_mod.replacement(&require.module, &require.version) {
+            Some((module, version)) => {
+                resolved.insert(module, (version, !require.indirect));
+            }
+            None => debug!("Skipping {} replaced by a local path", require.module),
+        }
+    }
+
+    // Older go.mod files only list direct requirements, the rest of the build
+    // list is only visible through the module hashes recorded in go.sum.
+    let go_sum_path = format!("{}/go.sum", path);
+    match read_to_string(&go_sum_path) {
+        Ok(go_sum) => {
+            let mut highest: HashMap<String, String> = HashMap::new();
+            for (module, version) in go_sum_modules(&go_sum) {
+                if let Some((module, version)) = go_mod.replacement(module, version) {
+                    keep_highest(&mut highest, module, version);
+                }
+            }
+            for (module, version) in highest {
+                resolved.entry(module).or_insert((version, false));
+            }
+        }
+        Err(_e) => debug!("Could not read go.sum at {}", go_sum_path),
+    }
+
+    // go.mod does not separate test-only requirements, every module is
+    // reported as a normal dependency. Neither file records which module
+    // requires which, so no edges are reported.
+    let dependencies: Vec<CollectorDependency> = resolved
+        .into_iter()
+        .map(|(module, (version, is_direct))| {
+            CollectorDependency::new(
+                module,
+                version,
+                ProjectLanguage::Go,
+                None,
+                DependencyKind::Normal,
+                is_direct,
+                Vec::new(),
+            )
+        })
+        .collect();
+
+    debug!("Found {} unique Go dependencies", dependencies.len());
+
+    Ok(dependencies)
+}
+
+fn parse_go_mod(contents: &str) -> GoModFile {
+    let mut go_mod = GoModFile::default();
+    let mut block: Option<&str> = None;
+
+    for line in contents.lines() {
+        let (line, indirect) = match line.find("//") {
+            Some(index) => (
+                &line[..index],
+                line[index + 2..].trim().starts_with("indirect"),
+            ),
+            None => (line, false),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(directive) = block {
+            if line == ")" {
+                block = None;
+            } else {
+                parse_directive(directive, line, indirect, &mut go_mod);
+            }
+            continue;
+        }
+
+        let Some((directive, rest)) = line.split_once(char::is_whitespace) else {
+            continue;
+        };
+        let rest = rest.trim();
+        if rest == "(" {
+            block = Some(directive);
+        } else {
+            parse_directive(directive, rest, indirect, &mut go_mod);
+        }
+    }
+
+    go_mod
+}
+
+fn parse_directive(directive: &str, line: &str, indirect: bool, go_mod: &mut GoModFile) {
+    match directive {
+        "require" => {
+            let mut parts = line.split_whitespace();
+            if let (Some(module), Some(version)) = (parts.next(), parts.next()) {
+                go_mod.requires.push(GoRequire {
+                    module: unquote(module),
+                    version: unquote(version),
+                    indirect,
+                });
+            }
+        }
+        "replace" => {
+            let Some((source, target)) = line.split_once("=>") else {
+                return;
+            };
+            let mut source = source.split_whitespace();
+            let mut target = target.split_whitespace();
+            let Some(module) = source.next().map(unquote) else {
+                return;
+            };
+            let version = source.next().map(unquote);
+            let target = match (target.next().map(unquote), target.next().map(unquote)) {
+                (Some(target_module), Some(target_version)) => {
+                    GoReplaceTarget::Module(target_module, target_version)
+                }
+                (Some(_), None) => GoReplaceTarget::LocalPath,
+                _ => return,
+            };
+            go_mod.replaces.push(GoReplace {
+                module,
+                version,
+                target,
+            });
+        }
+        _ => {}
+    }
+}
+
+fn unquote(value: &str) -> String {
+    value.trim_matches(['"', '`']).to_string()
+}
+
+/// The module versions that have a full source hash in go.sum. Entries
+/// ending in `/go.mod` only hash the module file and are recorded for modules
+/// that were never built.
+fn go_sum_modules(go_sum: &str) -> impl Iterator<Item = (&str, &str)> {
+    go_sum.lines().filter_map(|line| {
+        let mut parts = line.split_whitespace();
+        let (module, version) = (parts.next()?, parts.next()?);
+        (!version.ends_with("/go.mod")).then_some((module, version))
+    })
+}
+
+/// go.sum keeps the hashes of every version seen during resolution, minimal
+/// version selection builds with the highest of them.
+fn keep_highest(versions: &mut HashMap<String, String>, module: String, version: String) {
+    versions
+        .entry(module)
+        .and_modify(|current| {
+            if is_newer(&version, current) {
+                *current = version.clone();
+            }
+        })
+        .or_insert(version);
+}
+
+fn is_newer(candidate: &str, current: &str) -> bool {
+    let parse = |v: &str| Version::parse(v.trim_start_matches('v'));
+    match (parse(candidate), parse(current)) {
+        (Ok(candidate), Ok(current)) => candidate > current,
+        _ => candidate > current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/go_mod");
+
+    fn module(module: &str, version: &str) -> Option<(String, String)> {
+        Some((module.to_string(), version.to_string()))
+    }
+
+    #[test]
+    fn parses_block_and_single_line_directives() {
+        let contents = read_to_string(format!("{}/go.mod", FIXTURE)).unwrap();
+        let go_mod = parse_go_mod(&contents);
+
+        let requires: Vec<(&str, &str, bool)> = go_mod
+            .requires
+            .iter()
+            .map(|r| (r.module.as_str(), r.version.as_str(), r.indirect))
+            .collect();
+        assert_eq!(
+            requires,
+            [
+                ("github.com/pkg/errors", "v0.9.1", false),
+                ("github.com/quoted/mod", "v1.0.0", false),
+                ("github.com/stretchr/testify", "v1.8.4", false),
+                ("golang.org/x/text", "v0.14.0", true),
+                ("github.com/davecgh/go-spew", "v1.1.1", true),
+            ]
+        );
+
+        assert_eq!(go_mod.replaces.len(), 5);
+        let errors = &go_mod.replaces[0];
+        assert_eq!(errors.module, "github.com/pkg/errors");
+        assert_eq!(errors.version, None);
+        assert!(matches!(
+            &errors.target,
+            GoReplaceTarget::Module(target, version)
+                if target == "github.com/fork/errors" && version == "v0.9.2"
+        ));
+        assert!(matches!(
+            go_mod.replaces[1].target,
+            GoReplaceTarget::LocalPath
+        ));
+        assert_eq!(go_mod.replaces[4].version.as_deref(), Some("v0.10.0"));
+    }
+
+    #[test]
+    fn exact_version_replacement_wins() {
+        let go_mod = parse_go_mod(
+            "replace (\n\
+             \texample.com/a => example.com/a v1.5.0\n\
+             \texample.com/a v1.0.0 => example.com/a v1.1.0\n\
+             )\n",
+        );
+        assert_eq!(
+            go_mod.replacement("example.com/a", "v1.0.0"),
+            module("example.com/a", "v1.1.0")
+        );
+        assert_eq!(
+            go_mod.replacement("example.com/a", "v1.2.0"),
+            module("example.com/a", "v1.5.0")
+        );
+        assert_eq!(
+            go_mod.replacement("example.com/b", "v1.2.0"),
+            module("example.com/b", "v1.2.0")
+        );
+    }
+
+    #[test]
+    fn go_sum_modules_skip_go_mod_hashes() {
+        let go_sum = "a v1.0.0 h1:x=\na v1.1.0/go.mod h1:y=\n\nb v0.1.0 h1:z=\n";
+        assert_eq!(
+            go_sum_modules(go_sum).collect::<Vec<_>>(),
+            [("a", "v1.0.0"), ("b", "v0.1.0")]
+        );
+    }
+
+    #[test]
+    fn keeps_the_highest_version() {
+        let mut versions = HashMap::new();
+        for version in ["v1.9.0", "v1.10.0", "v1.2.0"] {
+            keep_highest(&mut versions, "a".to_string(), version.to_string());
+        }
+        assert_eq!(versions["a"], "v1.10.0");
+
+        assert!(is_newer("v1.0.0", "v1.0.0-rc.1"));
+        assert!(is_newer(
+            "v0.0.0-20231010120000-abcdef123456",
+            "v0.0.0-20230101120000-123456abcdef"
+        ));
+        assert!(is_newer("v2.1.0+incompatible", "v2.0.0+incompatible"));
+        assert!(!is_newer("v0.9.0", "v0.10.0"));
+    }
+
+    #[tokio::test]
+    async fn applies_replacements_to_go_mod_and_go_sum() {
+        let mut dependencies: Vec<(String, String, bool)> = collect_dependencies(FIXTURE)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|d| (d.name, d.version, d.is_direct))
+            .collect();
+        dependencies.sort();
+
+        let expected = [
+            ("github.com/davecgh/go-spew", "v1.1.1", false),
+            ("github.com/fork/errors", "v0.9.2", true),
+            ("github.com/new/lib", "v2.0.0+incompatible", false),
+            ("github.com/quoted/mod", "v1.0.0", true),
+            ("github.com/stretchr/testify", "v1.8.4", true),
+            // Pinned below the version go.sum also hashes.
+            ("golang.org/x/crypto", "v0.17.0", false),
+            // go.sum only hashes v0.10.0, which is replaced.
+            ("golang.org/x/net", "v0.17.0", false),
+            ("golang.org/x/sys", "v0.13.0", false),
+            ("golang.org/x/text", "v0.14.0", false),
+        ];
+        assert_eq!(
+            dependencies,
+            expected.map(|(name, version, is_direct)| (
+                name.to_string(),
+                version.to_string(),
+                is_direct
+            ))
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fs::{read_dir, read_to_string},
+    path::Path,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use log::debug;
+use serde_json::Value;
+
+use crate::{
+    collectors::{Collector, CollectorOutput, package_lock, pnpm_lock, yarn_lock},
+    models::{DependencyKind, ProjectLanguage},
+};
+
+/// When a project has more than one JavaScript lockfile and its package.json
+/// does not name a `packageManager`, the first lockfile in this list is used.
+const JAVASCRIPT_LOCKFILES: &[(&str, &str)] = &[
+    ("npm", "package-lock.json"),
+    ("pnpm", "pnpm-lock.yaml"),
+    ("yarn", "yarn.lock"),
+];
+
+const MANIFEST_DEPENDENCY_SECTIONS: &[(&str, DependencyKind)] = &[
+    ("dependencies", DependencyKind::Normal),
+    ("devDependencies", DependencyKind::Dev),
+    ("optionalDependencies", DependencyKind::Optional),
+    ("peerDependencies", DependencyKind::Peer),
+];
+
+pub struct JavascriptCollector;
+
+#[async_trait]
+impl Collector for JavascriptCollector {
+    fn language(&self) -> ProjectLanguage {
+        ProjectLanguage::Javascript
+    }
+
+    fn applies_to(&self, path: &Path) -> bool {
+        JAVASCRIPT_LOCKFILES
+            .iter()
+            .any(|(_, lockfile)| path.join(lockfile).is_file())
+    }
+
+    fn watches(&self, file_name: &str) -> bool {
+        file_name == "package.json"
+            || JAVASCRIPT_LOCKFILES
+                .iter()
+                .any(|(_, lockfile)| *lockfile == file_name)
+    }
+
+    async fn collect(&self, path: &str) -> Result<CollectorOutput> {
+        // Only a lockfile that exists is selected, so one that cannot be read
+        // is being rewritten and fails the scan rather than emptying it.
+        match select_lockfile(path) {
+            Some("pnpm-lock.yaml") => pnpm_lock::collect_dependencies(path).await,
+            Some("yarn.lock") => yarn_lock::collect_dependencies(path)
+                .await
+                .map(CollectorOutput::from),
+            Some(_) => package_lock::collect_dependencies(path).await,
+            None => {
+                debug!("No JavaScript lockfile found in {}", path);
+                Ok(CollectorOutput::default())
+            }
+        }
+    }
+}
+
+/// Returns where the lockfile's version of `name` is installed inside the
+/// project. npm and yarn hoist it into `node_modules/<name>`, pnpm keeps it in
+/// `node_modules/.pnpm/<name>@<version>/node_modules/<name>` with the scope's
+/// slash written as `+` and any peer dependencies appended to the directory.
+pub fn installed_package_path(path: &str, name: &str, version: &str) -> Option<String> {
+    let node_modules = Path::new(path).join("node_modules");
+    if let Some(found) = installed_package_at(&node_modules.join(name), name, version) {
+        return Some(found);
+    }
+
+    let store = node_modules.join(".pnpm");
+    let entry = format!("{}@{}", name.replace('/', "+"), version);
+    let in_store = |dir: &OsStr| {
+        installed_package_at(
+            &store.join(dir).join("node_modules").join(name),
+            name,
+            version,
+        )
+    };
+    if let Some(found) = in_store(OsStr::new(&entry)) {
+        return Some(found);
+    }
+    read_dir(&store)
+        .ok()?
+        .filter_map(|dir| dir.ok())
+        .map(|dir| dir.file_name())
+        .filter(|dir| {
+            dir.to_str()
+                .and_then(|dir| dir.strip_prefix(&entry))
+                .is_some_and(|peers| peers.starts_with(['_', '(']))
+        })
+        .find_map(|dir| in_store(&dir))
+}
+
+/// Returns `package_dir` when it holds exactly the locked version of `name`.
+pub fn installed_package_at(package_dir: &Path, name: &str, version: &str) -> Option<String> {
+    let manifest: Value =
+        serde_json::from_str(&read_to_string(package_dir.join("package.json")).ok()?).ok()?;
+    if manifest.get("version").and_then(Value::as_str) != Some(version) {
+        debug!(
+            "Installed {} in {} does not match locked version {}",
+            name,
+            package_dir.display(),
+            version
+        );
+        return None;
+    }
+    package_dir.to_str().map(str::to_string)
+}
+
+/// The `name` in the project's package.json.
+pub fn manifest_name(path: &str) -> Option<String> {
+    let manifest: Value =
+        serde_json::from_str(&read_to_string(Path::new(path).join("package.json")).ok()?).ok()?;
+    manifest.get("name")?.as_str().map(str::to_string)
+}
+
+/// The `(name, range, kind)` of every direct dependency in the project's
+/// package.json.
+pub fn declared_dependencies(path: &str) -> Vec<(String, String, DependencyKind)> {
+    let Some(manifest) = read_to_string(Path::new(path).join("package.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+    else {
+        return Vec::new();
+    };
+    let mut declared = Vec::new();
+    for (section, kind) in MANIFEST_DEPENDENCY_SECTIONS {
+        let Some(ranges) = manifest.get(section).and_then(Value::as_object) else {
+            continue;
+        };
+        for (name, range) in ranges {
+            let range = range.as_str().unwrap_or_default().to_string();
+            declared.push((name.clone(), range, *kind));
+        }
+    }
+    declared
+}
+
+/// Kinds of the direct dependencies declared in the project's package.json.
+pub fn declared_dependency_kinds(path: &str) -> HashMap<String, DependencyKind> {
+    let mut kinds: HashMap<String, DependencyKind> = HashMap::new();
+    for (name, _, kind) in declared_dependencies(path) {
+        kinds
+            .entry(name)
+            .and_modify(|existing| *existing = existing.strongest(kind))
+            .or_insert(kind);
+    }
+    kinds
+}
+
+/// Lockfile flags are used where the lockfile records them since they also
+/// cover transitive packages. Lockfiles never flag peer dependencies, and some
+/// formats carry no flags at all, so the package.json declaration fills in the
+/// kind of direct dependencies.
+pub fn resolve_kind(
+    locked: Option<DependencyKind>,
+    declared: Option<DependencyKind>,
+) -> DependencyKind {
+    match (locked, declared) {
+        (Some(DependencyKind::Normal), Some(DependencyKind::Peer)) => DependencyKind::Peer,
+        (Some(locked), _) => locked,
+        (None, declared) => declared.unwrap_or(DependencyKind::Normal),
+    }
+}
+
+fn select_lockfile(path: &str) -> Option<&'static str> {
+    let root = Path::new(path);
+    let present: Vec<(&str, &'static str)> = JAVASCRIPT_LOCKFILES
+        .iter()
+        .copied()
+        .filter(|(_, lockfile)| root.join(lockfile).is_file())
+        .collect();
+
+    let package_manager = read_to_string(root.join("package.json"))
+        .ok()
+        .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+        .and_then(|manifest| {
+            manifest
+                .get("packageManager")
+                .and_then(Value::as_str)
+                .and_then(|pm| pm.split('@').next())
+                .map(str::to_string)
+        });
+
+    if present.len() > 1 {
+        debug!(
+            "Found multiple JavaScript lockfiles in {}, package manager: {:?}",
+            path, package_manager
+        );
+    }
+
+    present
+        .iter()
+        .find(|(manager, _)| package_manager.as_deref() == Some(*manager))
+        .or_else(|| present.first())
+        .map(|(_, lockfile)| *lockfile)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+
+    fn install(dir: &Path, name: &str, version: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            dir.join("package.json"),
+            format!(r#"{{ "name": "{}", "version": "{}" }}"#, name, version),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn finds_hoisted_and_pnpm_installs() {
+        let root = env::temp_dir().join(format!("local-lore-installed-{}", process::id()));
+        let node_modules = root.join("node_modules");
+        let store = node_modules.join(".pnpm");
+        install(&node_modules.join("hoisted"), "hoisted", "1.0.0");
+        install(&node_modules.join("stale"), "stale", "1.0.0");
+        install(
+            &store.join("stale@2.0.0/node_modules/stale"),
+            "stale",
+            "2.0.0",
+        );
+        install(
+            &store.join("@scope+pkg@3.0.0/node_modules/@scope/pkg"),
+            "@scope/pkg",
+            "3.0.0",
+        );
+        install(
+            &store.join("peer@4.0.0_react@18.2.0/node_modules/peer"),
+            "peer",
+            "4.0.0",
+        );
+        install(
+            &store.join("peer@4.0.10/node_modules/peer"),
+            "peer",
+            "4.0.10",
+        );
+
+        let path = root.to_str().unwrap();
+        let found = |name: &str, version: &str| {
+            installed_package_path(path, name, version)
+                .map(|found| found.strip_prefix(path).unwrap().to_string())
+        };
+        let results = [
+            found("hoisted", "1.0.0"),
+            found("hoisted", "1.0.1"),
+            // The hoisted copy is another version than the locked one.
+            found("stale", "2.0.0"),
+            found("@scope/pkg", "3.0.0"),
+            found("peer", "4.0.0"),
+            found("missing", "1.0.0"),
+        ];
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            results,
+            [
+                Some("/node_modules/hoisted".to_string()),
+                None,
+                Some("/node_modules/.pnpm/stale@2.0.0/node_modules/stale".to_string()),
+                Some("/node_modules/.pnpm/@scope+pkg@3.0.0/node_modules/@scope/pkg".to_string()),
+                Some("/node_modules/.pnpm/peer@4.0.0_react@18.2.0/node_modules/peer".to_string()),
+                None,
+            ]
+        );
+    }
+}
@@ -1,80 +1,548 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet, VecDeque, hash_map::Entry},
+    path::Path,
+    sync::Arc,
+};
 
 use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
 use log::debug;
-use sea_orm::{EntityTrait, Set, sea_query::OnConflict};
+use sea_orm::{
+    ColumnTrait, DatabaseTransaction, EntityTrait, QueryFilter, Set, TransactionTrait,
+    sea_query::{Expr, OnConflict},
+};
+use semver::Version;
+use serde::Serialize;
 
 use crate::{
     LocalLoreContext,
     entities::{
-        dependency,
-        prelude::{Dependency, Project, ProjectDependency},
-        project, project_dependency,
+        dependency, dependency_edge,
+        prelude::{
+            Dependency, DependencyEdge, Project, ProjectDependency, ProjectDependencyChange,
+            ProjectScan,
+        },
+        project, project_dependency, project_dependency_change, project_scan,
     },
-    models::ProjectLanguage,
+    models::{DependencyChangeKind, DependencyKind, ProjectLanguage, ProjectLanguages},
 };
 
 pub mod cargo_lock;
+pub mod go_mod;
+pub mod javascript;
 pub mod package_lock;
+pub mod pnpm_lock;
+pub mod python;
+pub mod yarn_lock;
+
+const WRITE_BATCH_SIZE: usize = 500;
 
 #[derive(Debug, Clone)]
 pub struct CollectorDependency {
     pub name: String,
     pub version: String,
     pub language: ProjectLanguage,
+    pub source_path: Option<String>,
+    pub kind: DependencyKind,
+    /// Declared by the project itself rather than pulled in by another dependency.
+    pub is_direct: bool,
+    /// `(name, version)` of the dependencies this one pulls in.
+    pub depends_on: Vec<(String, String)>,
 }
 
 impl CollectorDependency {
-    pub fn new(name: String, version: String, language: ProjectLanguage) -> Self {
+    pub fn new(
+        name: String,
+        version: String,
+        language: ProjectLanguage,
+        source_path: Option<String>,
+        kind: DependencyKind,
+        is_direct: bool,
+        depends_on: Vec<(String, String)>,
+    ) -> Self {
         Self {
             name,
             version,
             language,
+            source_path,
+            kind,
+            is_direct,
+            depends_on,
+        }
+    }
+
+    /// Folds another occurrence of the same `(name, version)` into this one.
+    pub fn merge(&mut self, other: Self) {
+        self.kind = self.kind.strongest(other.kind);
+        self.is_direct |= other.is_direct;
+        if self.source_path.is_none() {
+            self.source_path = other.source_path;
+        }
+        for edge in other.depends_on {
+            if !self.depends_on.contains(&edge) {
+                self.depends_on.push(edge);
+            }
+        }
+    }
+}
+
+/// Collapses the entries a collector found for the same `(name, version)`,
+/// e.g. one package installed at several places in node_modules.
+pub fn dedupe_dependencies(
+    dependencies: impl IntoIterator<Item = CollectorDependency>,
+) -> Vec<CollectorDependency> {
+    let mut dependencies_map: HashMap<(String, String), CollectorDependency> = HashMap::new();
+    for dependency in dependencies {
+        match dependencies_map.entry((dependency.name.clone(), dependency.version.clone())) {
+            Entry::Occupied(mut existing) => existing.get_mut().merge(dependency),
+            Entry::Vacant(entry) => {
+                entry.insert(dependency);
+            }
+        }
+    }
+    dependencies_map.into_values().collect()
+}
+
+/// Collapses the entries several collectors or directories found for the same
+/// `(language, name, version)`.
+fn merge_language_dependencies(
+    dependencies: impl IntoIterator<Item = CollectorDependency>,
+) -> Vec<CollectorDependency> {
+    let mut dependencies_map: HashMap<(ProjectLanguage, String, String), CollectorDependency> =
+        HashMap::new();
+    for dependency in dependencies {
+        let key = (
+            dependency.language.clone(),
+            dependency.name.clone(),
+            dependency.version.clone(),
+        );
+        match dependencies_map.entry(key) {
+            Entry::Occupied(mut existing) => existing.get_mut().merge(dependency),
+            Entry::Vacant(entry) => {
+                entry.insert(dependency);
+            }
+        }
+    }
+    dependencies_map.into_values().collect()
+}
+
+/// For lockfiles that do not record why a package was installed, gives each
+/// transitive dependency the strongest kind of the direct dependencies it is
+/// reachable from. Packages only pulled in by test tooling become dev too.
+pub fn inherit_kinds(dependencies: &mut [CollectorDependency]) {
+    let index: HashMap<(String, String), usize> = dependencies
+        .iter()
+        .enumerate()
+        .map(|(i, d)| ((d.name.clone(), d.version.clone()), i))
+        .collect();
+    let mut inherited: Vec<Option<DependencyKind>> = vec![None; dependencies.len()];
+
+    for root in (0..dependencies.len()).filter(|&i| dependencies[i].is_direct) {
+        let kind = dependencies[root].kind;
+        let mut visited = HashSet::from([root]);
+        let mut queue = VecDeque::from([root]);
+        while let Some(current) = queue.pop_front() {
+            for child in &dependencies[current].depends_on {
+                let Some(&child) = index.get(child) else {
+                    continue;
+                };
+                if !visited.insert(child) {
+                    continue;
+                }
+                if !dependencies[child].is_direct {
+                    inherited[child] = Some(inherited[child].map_or(kind, |k| k.strongest(kind)));
+                }
+                queue.push_back(child);
+            }
+        }
+    }
+
+    for (dependency, kind) in dependencies.iter_mut().zip(inherited) {
+        if let Some(kind) = kind {
+            dependency.kind = kind;
+        }
+    }
+}
+
+/// A package inside a workspace that is recorded as its own project.
+#[derive(Debug, Clone)]
+pub struct WorkspaceMember {
+    pub name: String,
+    pub path: String,
+    pub dependencies: Vec<CollectorDependency>,
+}
+
+/// Everything a collector found for the scanned path. For workspaces the
+/// dependencies cover all members and each member is reported again with
+/// only what it pulls in itself.
+#[derive(Debug, Default)]
+pub struct CollectorOutput {
+    pub dependencies: Vec<CollectorDependency>,
+    pub members: Vec<WorkspaceMember>,
+}
+
+impl From<Vec<CollectorDependency>> for CollectorOutput {
+    fn from(dependencies: Vec<CollectorDependency>) -> Self {
+        Self {
+            dependencies,
+            members: Vec::new(),
+        }
+    }
+}
+
+#[async_trait]
+pub trait Collector: Send + Sync {
+    /// The ecosystem this collector reports dependencies for.
+    fn language(&self) -> ProjectLanguage;
+
+    /// Whether the project at `path` has the manifests this collector reads.
+    fn applies_to(&self, path: &Path) -> bool;
+
+    /// Whether a change to this file at the project root can change what
+    /// `collect` reports.
+    fn watches(&self, file_name: &str) -> bool;
+
+    async fn collect(&self, path: &str) -> Result<CollectorOutput>;
+}
+
+/// Narrows the dependencies of a whole workspace down to what one member
+/// pulls in: its own direct dependencies and everything reachable from them.
+pub fn member_dependencies(
+    workspace: &[CollectorDependency],
+    direct_kinds: &HashMap<(String, String), DependencyKind>,
+) -> Vec<CollectorDependency> {
+    let index: HashMap<(&str, &str), &CollectorDependency> = workspace
+        .iter()
+        .map(|d| ((d.name.as_str(), d.version.as_str()), d))
+        .collect();
+    let mut visited = HashSet::new();
+    let mut queue: VecDeque<(&str, &str)> = direct_kinds
+        .keys()
+        .map(|(name, version)| (name.as_str(), version.as_str()))
+        .collect();
+    let mut dependencies = Vec::new();
+    while let Some(key) = queue.pop_front() {
+        if !visited.insert(key) {
+            continue;
+        }
+        let Some(&dependency) = index.get(&key) else {
+            continue;
+        };
+        queue.extend(
+            dependency
+                .depends_on
+                .iter()
+                .map(|(name, version)| (name.as_str(), version.as_str())),
+        );
+        let mut dependency = dependency.clone();
+        let direct_kind = direct_kinds.get(&(dependency.name.clone(), dependency.version.clone()));
+        dependency.is_direct = direct_kind.is_some();
+        if let Some(kind) = direct_kind {
+            dependency.kind = *kind;
         }
+        dependencies.push(dependency);
+    }
+    dependencies
+}
+
+pub struct CollectorRegistry {
+    collectors: Vec<Box<dyn Collector>>,
+}
+
+impl CollectorRegistry {
+    pub fn builtin() -> Self {
+        let mut registry = Self {
+            collectors: Vec::new(),
+        };
+        registry
+            .register(cargo_lock::CargoLockCollector)
+            .register(javascript::JavascriptCollector)
+            .register(python::PythonCollector)
+            .register(go_mod::GoModCollector);
+        registry
+    }
+
+    /// Keeps only the collectors of the given languages.
+    pub fn enabled(mut self, languages: &[ProjectLanguage]) -> Self {
+        self.collectors
+            .retain(|c| languages.contains(&c.language()));
+        self
+    }
+
+    pub fn register(&mut self, collector: impl Collector + 'static) -> &mut Self {
+        self.collectors.push(Box::new(collector));
+        self
+    }
+
+    /// Whether any collector reads the file with this name.
+    pub fn watches(&self, file_name: &str) -> bool {
+        self.collectors.iter().any(|c| c.watches(file_name))
     }
+
+    pub fn detect(&self, path: &Path) -> Vec<&dyn Collector> {
+        self.collectors
+            .iter()
+            .map(|c| c.as_ref())
+            .filter(|c| c.applies_to(path))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScanSummary {
+    pub languages: Vec<ProjectLanguage>,
+    pub total_dependencies: usize,
+    pub workspace_members: usize,
 }
 
-pub async fn gather_project_dependencies(path: &str, ctx: &Arc<LocalLoreContext>) -> Result<()> {
+pub async fn gather_project_dependencies(
+    path: &str,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<ScanSummary> {
+    let collectors = ctx.collectors.detect(Path::new(path));
+    let mut languages: Vec<ProjectLanguage> = collectors.iter().map(|c| c.language()).collect();
+    debug!("Detected ecosystems for {}: {:?}", path, languages);
+
     let project_name = Path::new(path)
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or(path)
         .to_string();
 
+    let mut all_dependencies = Vec::new();
+    let mut members: HashMap<String, (WorkspaceMember, Vec<ProjectLanguage>)> = HashMap::new();
+
+    for collector in collectors {
+        let output = collector.collect(path).await?;
+        all_dependencies.extend(output.dependencies);
+        for member in output.members {
+            // A workspace root that is a package itself is the project.
+            if member.path == path {
+                continue;
+            }
+            match members.entry(member.path.clone()) {
+                Entry::Occupied(mut existing) => {
+                    let (existing, member_languages) = existing.get_mut();
+                    existing.dependencies.extend(member.dependencies);
+                    member_languages.push(collector.language());
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert((member, vec![collector.language()]));
+                }
+            }
+        }
+    }
+
+    if let Some(project_config) = ctx.config.project(path) {
+        for dir in project_config.extra_dirs() {
+            let Some(dir) = dir.to_str() else {
+                continue;
+            };
+            for collector in ctx.collectors.detect(Path::new(dir)) {
+                debug!(
+                    "Collecting {:?} dependencies of {} from {}",
+                    collector.language(),
+                    path,
+                    dir
+                );
+                all_dependencies.extend(collector.collect(dir).await?.dependencies);
+                if !languages.contains(&collector.language()) {
+                    languages.push(collector.language());
+                }
+            }
+        }
+        all_dependencies = merge_language_dependencies(all_dependencies);
+
+        all_dependencies.retain(|d| !project_config.ignores(&d.name));
+        for (member, _) in members.values_mut() {
+            member
+                .dependencies
+                .retain(|d| !project_config.ignores(&d.name));
+        }
+    }
+
+    let project_id = store_project(
+        path,
+        project_name,
+        None,
+        languages.clone(),
+        &all_dependencies,
+        ctx,
+    )
+    .await?;
+
+    let workspace_members = members.len();
+    detach_dropped_members(project_id, members.keys(), ctx).await?;
+    for (member, member_languages) in members.into_values() {
+        debug!(
+            "Recording workspace member {} at {}",
+            member.name, member.path
+        );
+        store_project(
+            &member.path,
+            member.name,
+            Some(project_id),
+            member_languages,
+            &member.dependencies,
+            ctx,
+        )
+        .await?;
+    }
+
+    debug!(
+        "Scan completed for: {} and updated {} dependencies",
+        path,
+        all_dependencies.len()
+    );
+
+    Ok(ScanSummary {
+        languages,
+        total_dependencies: all_dependencies.len(),
+        workspace_members,
+    })
+}
+
+/// Members the workspace no longer lists keep their row and history, but
+/// lose their dependencies and are marked missing until they are found again.
+/// Members whose directory vanished were already marked by the rescan.
+async fn detach_dropped_members(
+    project_id: i32,
+    member_paths: impl Iterator<Item = &String>,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<()> {
+    let member_paths: HashSet<&String> = member_paths.collect();
+    let dropped: Vec<project::Model> = Project::find()
+        .filter(project::Column::ParentId.eq(project_id))
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .filter(|member| !member_paths.contains(&member.path))
+        .collect();
+
+    for member in dropped {
+        debug!(
+            "Workspace member {} at {} was dropped",
+            member.name, member.path
+        );
+        let txn = ctx.db.begin().await?;
+        let previous_links: HashMap<i32, project_dependency::Model> = ProjectDependency::find()
+            .filter(project_dependency::Column::ProjectId.eq(member.id))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|link| (link.dependency_id, link))
+            .collect();
+        reconcile_project_dependencies(
+            member.id,
+            previous_links,
+            &HashSet::new(),
+            Vec::new(),
+            &txn,
+        )
+        .await?;
+        replace_dependency_edges(member.id, HashSet::new(), &txn).await?;
+        Project::update_many()
+            .col_expr(project::Column::MissingSince, Expr::value(Utc::now()))
+            .filter(project::Column::Id.eq(member.id))
+            .filter(project::Column::MissingSince.is_null())
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+    }
+    Ok(())
+}
+
+/// Marks projects whose directory has vanished as missing and returns the
+/// paths of the workspaces and standalone projects that are still on disk.
+/// Members are left out since scanning their workspace covers them.
+pub async fn known_project_paths(ctx: &Arc<LocalLoreContext>) -> Result<Vec<String>> {
+    let projects = Project::find().all(&ctx.db).await?;
+    let (present, vanished): (Vec<project::Model>, Vec<project::Model>) = projects
+        .into_iter()
+        .partition(|p| Path::new(&p.path).is_dir());
+
+    let newly_missing: Vec<i32> = vanished
+        .iter()
+        .filter(|p| p.missing_since.is_none())
+        .map(|p| p.id)
+        .collect();
+    if !newly_missing.is_empty() {
+        debug!(
+            "Marking {} vanished projects as missing",
+            newly_missing.len()
+        );
+        Project::update_many()
+            .col_expr(project::Column::MissingSince, Expr::value(Utc::now()))
+            .filter(project::Column::Id.is_in(newly_missing))
+            .exec(&ctx.db)
+            .await?;
+    }
+
+    Ok(present
+        .into_iter()
+        .filter(|p| p.parent_id.is_none())
+        .map(|p| p.path)
+        .collect())
+}
+
+/// Upserts the project row along with its dependencies and their edges in one
+/// transaction, so a failed scan leaves the previous one intact. Only
+/// workspace members carry a parent, scanning a member on its own keeps the
+/// link to its workspace.
+async fn store_project(
+    path: &str,
+    name: String,
+    parent_id: Option<i32>,
+    languages: Vec<ProjectLanguage>,
+    all_dependencies: &[CollectorDependency],
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<i32> {
+    let txn = ctx.db.begin().await?;
     let new_project = project::ActiveModel {
-        name: Set(project_name),
+        name: Set(name),
         path: Set(path.to_string()),
+        languages: Set(ProjectLanguages(languages)),
+        parent_id: Set(parent_id),
+        missing_since: Set(None),
         ..Default::default()
     };
+    let mut update_columns = vec![
+        project::Column::LastSeenAt,
+        project::Column::Languages,
+        project::Column::MissingSince,
+    ];
+    if parent_id.is_some() {
+        update_columns.push(project::Column::ParentId);
+    }
     let project_record = Project::insert(new_project)
         .on_conflict(
             OnConflict::column(project::Column::Path)
-                .update_column(project::Column::LastSeenAt)
+                .update_columns(update_columns)
                 .to_owned(),
         )
-        .exec_with_returning(&ctx.db)
+        .exec_with_returning(&txn)
         .await?;
 
-    let mut all_dependencies = Vec::new();
-
-    let rust_deps = cargo_lock::collect_dependencies(path).await?;
-    all_dependencies.extend(rust_deps);
-
-    let js_deps = package_lock::collect_dependencies(path).await?;
-    all_dependencies.extend(js_deps);
-
     debug!("Processing {} dependencies", all_dependencies.len());
 
-    let mut total_updated = 0;
-    for dep_input in all_dependencies {
-        let dep_name = dep_input.name;
-        let dep_version = dep_input.version;
-        let dep_language = dep_input.language;
+    let previous_links: HashMap<i32, project_dependency::Model> = ProjectDependency::find()
+        .filter(project_dependency::Column::ProjectId.eq(project_record.id))
+        .all(&txn)
+        .await?
+        .into_iter()
+        .map(|link| (link.dependency_id, link))
+        .collect();
 
+    let mut dependency_ids: HashMap<(ProjectLanguage, String, String), i32> = HashMap::new();
+    let mut added = Vec::new();
+    for dep_input in all_dependencies {
         let new_dep = dependency::ActiveModel {
-            name: Set(dep_name.clone()),
-            version: Set(dep_version.clone()),
-            language: Set(dep_language.clone()),
+            name: Set(dep_input.name.clone()),
+            version: Set(dep_input.version.clone()),
+            language: Set(dep_input.language.clone()),
+            source_path: Set(dep_input.source_path.clone()),
             ..Default::default()
         };
         let dependency_record = Dependency::insert(new_dep)
@@ -85,14 +553,30 @@ pub async fn gather_project_dependencies(path: &str, ctx: &Arc<LocalLoreContext>
                     dependency::Column::Language,
                 ])
                 .update_column(dependency::Column::LastSeenAt)
+                .value(
+                    dependency::Column::SourcePath,
+                    Expr::cust("COALESCE(excluded.source_path, dependency.source_path)"),
+                )
+                // A dependency indexed before it was found installed is
+                // indexed again from the install.
+                .value(
+                    dependency::Column::LastIndexedAt,
+                    Expr::cust(
+                        "CASE WHEN dependency.source_path IS NULL \
+                         AND excluded.source_path IS NOT NULL \
+                         THEN NULL ELSE dependency.last_indexed_at END",
+                    ),
+                )
                 .to_owned(),
             )
-            .exec_with_returning(&ctx.db)
+            .exec_with_returning(&txn)
             .await?;
 
         let new_project_dep = project_dependency::ActiveModel {
             project_id: Set(project_record.id),
             dependency_id: Set(dependency_record.id),
+            kind: Set(dep_input.kind),
+            is_direct: Set(dep_input.is_direct),
             ..Default::default()
         };
         ProjectDependency::insert(new_project_dep)
@@ -101,19 +585,503 @@ pub async fn gather_project_dependencies(path: &str, ctx: &Arc<LocalLoreContext>
                     project_dependency::Column::ProjectId,
                     project_dependency::Column::DependencyId,
                 ])
-                .update_column(project_dependency::Column::LastSeenAt)
+                .update_columns([
+                    project_dependency::Column::LastSeenAt,
+                    project_dependency::Column::Kind,
+                    project_dependency::Column::IsDirect,
+                ])
                 .to_owned(),
             )
-            .exec(&ctx.db)
+            .exec(&txn)
             .await?;
 
-        total_updated += 1;
+        if !previous_links.contains_key(&dependency_record.id) {
+            added.push((dependency_record.id, dep_input));
+        }
+
+        dependency_ids.insert(
+            (
+                dep_input.language.clone(),
+                dep_input.name.clone(),
+                dep_input.version.clone(),
+            ),
+            dependency_record.id,
+        );
+    }
+
+    let seen: HashSet<i32> = dependency_ids.values().copied().collect();
+    reconcile_project_dependencies(project_record.id, previous_links, &seen, added, &txn).await?;
+
+    let mut edges = HashSet::new();
+    for dep_input in all_dependencies {
+        let parent_key = (
+            dep_input.language.clone(),
+            dep_input.name.clone(),
+            dep_input.version.clone(),
+        );
+        let Some(&parent_id) = dependency_ids.get(&parent_key) else {
+            continue;
+        };
+        for (name, version) in &dep_input.depends_on {
+            let child_key = (dep_input.language.clone(), name.clone(), version.clone());
+            match dependency_ids.get(&child_key) {
+                Some(&child_id) if child_id != parent_id => {
+                    edges.insert((parent_id, child_id));
+                }
+                Some(_) => {}
+                None => debug!(
+                    "Skipping edge from {} {} to unresolved {} {}",
+                    dep_input.name, dep_input.version, name, version
+                ),
+            }
+        }
+    }
+    replace_dependency_edges(project_record.id, edges, &txn).await?;
+
+    txn.commit().await?;
+    Ok(project_record.id)
+}
+
+/// Drops the links to dependencies the scan no longer found and records the
+/// scan with what it added and removed in the project's change history. A
+/// version of a package replacing another one is recorded as an upgrade or a
+/// downgrade instead.
+async fn reconcile_project_dependencies(
+    project_id: i32,
+    previous_links: HashMap<i32, project_dependency::Model>,
+    seen: &HashSet<i32>,
+    added: Vec<(i32, &CollectorDependency)>,
+    txn: &DatabaseTransaction,
+) -> Result<()> {
+    let stale: Vec<project_dependency::Model> = previous_links
+        .into_values()
+        .filter(|link| !seen.contains(&link.dependency_id))
+        .collect();
+    let stale_dependency_ids: Vec<i32> = stale.iter().map(|link| link.dependency_id).collect();
+    let mut stale_dependencies: HashMap<i32, dependency::Model> = HashMap::new();
+    for batch in stale_dependency_ids.chunks(WRITE_BATCH_SIZE) {
+        for dependency in Dependency::find()
+            .filter(dependency::Column::Id.is_in(batch.iter().copied()))
+            .all(txn)
+            .await?
+        {
+            stale_dependencies.insert(dependency.id, dependency);
+        }
+    }
+
+    type VersionChanges<'a> = (
+        Vec<(&'a str, i32, &'a CollectorDependency)>,
+        Vec<(&'a str, &'a project_dependency::Model)>,
+    );
+    let mut by_package: HashMap<(ProjectLanguage, &str), VersionChanges> = HashMap::new();
+    for (dependency_id, dep_input) in &added {
+        by_package
+            .entry((dep_input.language.clone(), dep_input.name.as_str()))
+            .or_default()
+            .0
+            .push((dep_input.version.as_str(), *dependency_id, dep_input));
+    }
+    for link in &stale {
+        let Some(dependency) = stale_dependencies.get(&link.dependency_id) else {
+            continue;
+        };
+        by_package
+            .entry((dependency.language.clone(), dependency.name.as_str()))
+            .or_default()
+            .1
+            .push((dependency.version.as_str(), link));
+    }
+
+    let change = |dependency_id: i32,
+                  change: DependencyChangeKind,
+                  kind: DependencyKind,
+                  is_direct: bool,
+                  previous_dependency_id: Option<i32>| {
+        project_dependency_change::ActiveModel {
+            project_id: Set(project_id),
+            dependency_id: Set(dependency_id),
+            change: Set(change),
+            kind: Set(kind),
+            is_direct: Set(is_direct),
+            previous_dependency_id: Set(previous_dependency_id),
+            ..Default::default()
+        }
+    };
+    let mut changes = Vec::new();
+    for (mut added, mut removed) in by_package.into_values() {
+        // Several versions of one package can change at once, each new
+        // version replaces the closest old one so 1.0 and 2.0 moving to 2.1
+        // reads as 2.0 upgraded and 1.0 removed.
+        added.sort_by(|a, b| compare_versions(a.0, b.0));
+        removed.sort_by(|a, b| compare_versions(a.0, b.0));
+        let added_versions: Vec<&str> = added.iter().map(|a| a.0).collect();
+        let removed_versions: Vec<&str> = removed.iter().map(|r| r.0).collect();
+        let mut added_paired = vec![false; added.len()];
+        let mut removed_paired = vec![false; removed.len()];
+        for (a, r) in pair_versions(&added_versions, &removed_versions) {
+            added_paired[a] = true;
+            removed_paired[r] = true;
+            let (version, dependency_id, dep_input) = added[a];
+            let (previous_version, link) = removed[r];
+            let kind = match compare_versions(version, previous_version) {
+                Ordering::Less => DependencyChangeKind::Downgraded,
+                _ => DependencyChangeKind::Upgraded,
+            };
+            changes.push(change(
+                dependency_id,
+                kind,
+                dep_input.kind,
+                dep_input.is_direct,
+                Some(link.dependency_id),
+            ));
+        }
+        for ((_, dependency_id, dep_input), _) in
+            added.iter().zip(added_paired).filter(|(_, paired)| !paired)
+        {
+            changes.push(change(
+                *dependency_id,
+                DependencyChangeKind::Added,
+                dep_input.kind,
+                dep_input.is_direct,
+                None,
+            ));
+        }
+        for ((_, link), _) in removed
+            .iter()
+            .zip(removed_paired)
+            .filter(|(_, paired)| !paired)
+        {
+            changes.push(change(
+                link.dependency_id,
+                DependencyChangeKind::Removed,
+                link.kind,
+                link.is_direct,
+                None,
+            ));
+        }
     }
 
+    let count = |kind: DependencyChangeKind| {
+        changes
+            .iter()
+            .filter(|c| c.change.as_ref() == &kind)
+            .count() as i32
+    };
+    let scan = project_scan::ActiveModel {
+        project_id: Set(project_id),
+        scanned_at: Set(Utc::now()),
+        added: Set(count(DependencyChangeKind::Added)),
+        removed: Set(count(DependencyChangeKind::Removed)),
+        upgraded: Set(count(DependencyChangeKind::Upgraded)),
+        downgraded: Set(count(DependencyChangeKind::Downgraded)),
+        ..Default::default()
+    };
     debug!(
-        "Scan completed for: {} and updated {} dependencies",
-        path, total_updated
+        "Recording scan with {} added, {} removed, {} upgraded and {} downgraded dependencies",
+        scan.added.as_ref(),
+        scan.removed.as_ref(),
+        scan.upgraded.as_ref(),
+        scan.downgraded.as_ref()
     );
 
+    let scan = ProjectScan::insert(scan).exec_with_returning(txn).await?;
+    let stale_ids: Vec<i32> = stale.iter().map(|link| link.id).collect();
+    for batch in stale_ids.chunks(WRITE_BATCH_SIZE) {
+        ProjectDependency::delete_many()
+            .filter(project_dependency::Column::Id.is_in(batch.iter().copied()))
+            .exec(txn)
+            .await?;
+    }
+    for change in &mut changes {
+        change.scan_id = Set(Some(scan.id));
+    }
+    for batch in changes.chunks(WRITE_BATCH_SIZE) {
+        ProjectDependencyChange::insert_many(batch.to_vec())
+            .exec(txn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Orders versions by their numeric segments where both are semver or close
+/// to it, e.g. Python's `2.9` before `2.10`. Otherwise the versions are
+/// compared segment by segment, where a text segment marks a pre-release, so
+/// `1.0rc1` comes before `1.0` but after `1.0b2`.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| Version::parse(v.trim_start_matches('v'));
+    if let (Ok(a), Ok(b)) = (parse(a), parse(b)) {
+        return a.cmp(&b);
+    }
+    let (a, b) = (version_segments(a), version_segments(b));
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            // A missing release number counts as 0, so `1.0` equals `1.0.0`.
+            let pad = |other: Option<&VersionSegment>| match other {
+                Some(VersionSegment::Number(_)) => VersionSegment::Number(0),
+                _ => VersionSegment::End,
+            };
+            let (a_segment, b_segment) = (a.get(i), b.get(i));
+            let a_padded = a_segment.copied().unwrap_or_else(|| pad(b_segment));
+            let b_padded = b_segment.copied().unwrap_or_else(|| pad(a_segment));
+            a_padded.cmp(&b_padded)
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// A pre-release tag sorts before the end of a version, which sorts before a
+/// further release number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum VersionSegment<'a> {
+    Text(&'a str),
+    End,
+    Number(u64),
+}
+
+/// Splits a version into its runs of digits and of letters, e.g. `1.0rc1`
+/// into `1`, `0`, `rc` and `1`.
+fn version_segments(version: &str) -> Vec<VersionSegment<'_>> {
+    let version = version.trim_start_matches('v');
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let class = |c: char| (c.is_ascii_digit(), c.is_ascii_alphabetic());
+    for (index, c) in version.char_indices().chain([(version.len(), '.')]) {
+        let run = &version[start..index];
+        if run
+            .chars()
+            .next()
+            .is_some_and(|first| class(first) == class(c))
+        {
+            continue;
+        }
+        if let Ok(number) = run.parse() {
+            segments.push(VersionSegment::Number(number));
+        } else if run.chars().all(|c| c.is_ascii_alphabetic()) && !run.is_empty() {
+            segments.push(VersionSegment::Text(run));
+        }
+        start = index;
+    }
+    segments
+}
+
+/// Pairs each added version with the removed version it most likely
+/// replaces, returning their indices. Versions sharing a major are paired
+/// first, closest ones before the rest, whatever is left over is then paired
+/// across majors.
+fn pair_versions(added: &[&str], removed: &[&str]) -> Vec<(usize, usize)> {
+    let added: Vec<Vec<u64>> = added.iter().map(|v| numeric_segments(v)).collect();
+    let removed: Vec<Vec<u64>> = removed.iter().map(|v| numeric_segments(v)).collect();
+    let mut added_left: Vec<usize> = (0..added.len()).collect();
+    let mut removed_left: Vec<usize> = (0..removed.len()).collect();
+    let mut pairs = Vec::new();
+    for same_major in [true, false] {
+        loop {
+            let closest = added_left
+                .iter()
+                .flat_map(|&a| removed_left.iter().map(move |&r| (a, r)))
+                .filter(|&(a, r)| !same_major || added[a].first() == removed[r].first())
+                .min_by_key(|&(a, r)| version_distance(&added[a], &removed[r]));
+            let Some((a, r)) = closest else {
+                break;
+            };
+            added_left.retain(|&i| i != a);
+            removed_left.retain(|&i| i != r);
+            pairs.push((a, r));
+        }
+    }
+    pairs
+}
+
+/// The leading numeric segments of a version, e.g. `[1, 2, 3]` for `v1.2.3-rc.1`.
+fn numeric_segments(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .map_while(|segment| segment.parse().ok())
+        .collect()
+}
+
+/// How far apart two versions are, segment by segment, so a different minor
+/// outweighs any number of patch releases.
+fn version_distance(a: &[u64], b: &[u64]) -> Vec<u64> {
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            let (a, b) = (
+                a.get(i).copied().unwrap_or(0),
+                b.get(i).copied().unwrap_or(0),
+            );
+            a.abs_diff(b)
+        })
+        .collect()
+}
+
+/// The resolved graph is rebuilt from scratch on every scan of a project.
+async fn replace_dependency_edges(
+    project_id: i32,
+    edges: HashSet<(i32, i32)>,
+    txn: &DatabaseTransaction,
+) -> Result<()> {
+    let edges: Vec<dependency_edge::ActiveModel> = edges
+        .into_iter()
+        .map(|(parent_id, child_id)| dependency_edge::ActiveModel {
+            project_id: Set(project_id),
+            parent_id: Set(parent_id),
+            child_id: Set(child_id),
+            ..Default::default()
+        })
+        .collect();
+    debug!("Storing {} dependency edges", edges.len());
+
+    DependencyEdge::delete_many()
+        .filter(dependency_edge::Column::ProjectId.eq(project_id))
+        .exec(txn)
+        .await?;
+    for batch in edges.chunks(WRITE_BATCH_SIZE) {
+        DependencyEdge::insert_many(batch.to_vec())
+            .exec(txn)
+            .await?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use sea_orm::PaginatorTrait;
+
+    use super::*;
+    use crate::config::Config;
+
+    fn dependency(
+        name: &str,
+        kind: DependencyKind,
+        is_direct: bool,
+        depends_on: &[&str],
+    ) -> CollectorDependency {
+        CollectorDependency::new(
+            name.to_string(),
+            "1.0.0".to_string(),
+            ProjectLanguage::Javascript,
+            None,
+            kind,
+            is_direct,
+            depends_on
+                .iter()
+                .map(|name| (name.to_string(), "1.0.0".to_string()))
+                .collect(),
+        )
+    }
+
+    fn kind_of(dependencies: &[CollectorDependency], name: &str) -> DependencyKind {
+        dependencies.iter().find(|d| d.name == name).unwrap().kind
+    }
+
+    #[test]
+    fn transitive_dependencies_inherit_the_strongest_kind() {
+        let mut dependencies = vec![
+            dependency("app-lib", DependencyKind::Normal, true, &["shared"]),
+            dependency(
+                "test-runner",
+                DependencyKind::Dev,
+                true,
+                &["shared", "assert"],
+            ),
+            dependency("shared", DependencyKind::Dev, false, &["leaf"]),
+            dependency("assert", DependencyKind::Normal, false, &["leaf", "cycle"]),
+            dependency("leaf", DependencyKind::Normal, false, &[]),
+            dependency("cycle", DependencyKind::Normal, false, &["assert"]),
+            dependency("orphan", DependencyKind::Optional, false, &[]),
+        ];
+        inherit_kinds(&mut dependencies);
+
+        assert_eq!(kind_of(&dependencies, "app-lib"), DependencyKind::Normal);
+        assert_eq!(kind_of(&dependencies, "test-runner"), DependencyKind::Dev);
+        // Reachable from both a normal and a dev dependency.
+        assert_eq!(kind_of(&dependencies, "shared"), DependencyKind::Normal);
+        assert_eq!(kind_of(&dependencies, "leaf"), DependencyKind::Normal);
+        // Only pulled in by test tooling.
+        assert_eq!(kind_of(&dependencies, "assert"), DependencyKind::Dev);
+        assert_eq!(kind_of(&dependencies, "cycle"), DependencyKind::Dev);
+        // Not reachable from any direct dependency, keeps its own kind.
+        assert_eq!(kind_of(&dependencies, "orphan"), DependencyKind::Optional);
+    }
+
+    #[test]
+    fn compares_versions() {
+        assert_eq!(compare_versions("2.9", "2.10"), Ordering::Less);
+        assert_eq!(compare_versions("2.9.0", "2.10.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.10.0.1", "1.9.0.2"), Ordering::Greater);
+        assert_eq!(compare_versions("v1.2.3", "1.2.3"), Ordering::Equal);
+        assert_eq!(compare_versions("v1.2.3", "v1.2.10"), Ordering::Less);
+        assert_eq!(compare_versions("v2.0", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.0-rc.1", "1.0.0"), Ordering::Less);
+        assert_eq!(
+            compare_versions("1.0.0-alpha", "1.0.0-beta"),
+            Ordering::Less
+        );
+        assert_eq!(compare_versions("1.0rc1", "1.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0b2", "1.0rc1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0a1", "1.0a2"), Ordering::Less);
+        assert_eq!(compare_versions("1.0rc1", "1.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn pairs_versions_within_a_major_first() {
+        assert_eq!(pair_versions(&["2.1"], &["1.0", "2.0"]), [(0, 1)]);
+        assert_eq!(pair_versions(&["1.6.0"], &["1.0.0", "1.5.0"]), [(0, 1)]);
+        assert_eq!(
+            pair_versions(&["1.1.0", "2.1.0"], &["1.0.0", "2.0.0"]),
+            [(0, 0), (1, 1)]
+        );
+        // A major upgrade is still paired when nothing closer is left.
+        assert_eq!(pair_versions(&["v3.0.0"], &["v2.4.1"]), [(0, 0)]);
+        assert_eq!(pair_versions(&["1.0"], &[]), []);
+    }
+
+    #[test]
+    fn direct_dependencies_keep_their_own_kind() {
+        let mut dependencies = vec![
+            dependency("app", DependencyKind::Normal, true, &["tool"]),
+            dependency("tool", DependencyKind::Dev, true, &[]),
+        ];
+        inherit_kinds(&mut dependencies);
+        assert_eq!(kind_of(&dependencies, "tool"), DependencyKind::Dev);
+    }
+
+    #[tokio::test]
+    async fn unreadable_lockfile_fails_the_scan_and_keeps_the_links() {
+        let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/go_mod");
+        let dir =
+            std::env::temp_dir().join(format!("local-lore-unreadable-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for file in ["go.mod", "go.sum"] {
+            fs::copy(Path::new(fixture).join(file), dir.join(file)).unwrap();
+        }
+        let path = dir.to_str().unwrap();
+        let ctx = LocalLoreContext::in_memory(Config::default()).await;
+
+        let summary = gather_project_dependencies(path, &ctx).await.unwrap();
+        let links = ProjectDependency::find().count(&ctx.db).await.unwrap();
+        assert_eq!(links, summary.total_dependencies as u64);
+
+        // Not valid UTF-8, as if caught in the middle of being written.
+        fs::write(dir.join("go.mod"), [0xff, 0xfe, 0x00]).unwrap();
+        let result = gather_project_dependencies(path, &ctx).await;
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(result.is_err());
+        assert_eq!(
+            ProjectDependency::find().count(&ctx.db).await.unwrap(),
+            links
+        );
+        assert_eq!(
+            ProjectDependencyChange::find()
+                .count(&ctx.db)
+                .await
+                .unwrap(),
+            links
+        );
+    }
+}
@@ -1,53 +1,379 @@
-use std::{collections::HashMap, fs::read_to_string};
+use std::{collections::HashMap, fs::read_to_string, path::Path};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use log::debug;
-use package_lock_json_parser::parse_dependencies;
+use serde::Deserialize;
 
-use crate::{collectors::CollectorDependency, models::ProjectLanguage};
+use crate::{
+    collectors::{
+        CollectorDependency, CollectorOutput, WorkspaceMember, dedupe_dependencies, inherit_kinds,
+        javascript::{declared_dependency_kinds, installed_package_at, resolve_kind},
+        member_dependencies,
+    },
+    models::{DependencyKind, ProjectLanguage},
+};
 
-pub async fn collect_dependencies(path: &str) -> Result<Vec<CollectorDependency>> {
+const NODE_MODULES: &str = "node_modules/";
+
+#[derive(Debug, Deserialize)]
+struct PackageLockFile {
+    /// Lockfile v2 and v3 list every installed package keyed by its path,
+    /// e.g. `node_modules/a/node_modules/b`. The root project has the key "".
+    packages: Option<HashMap<String, PackageLockEntry>>,
+    /// Lockfile v1 only has the nested dependency tree.
+    #[serde(default)]
+    dependencies: HashMap<String, PackageLockV1Entry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PackageLockEntry {
+    name: Option<String>,
+    version: Option<String>,
+    #[serde(default)]
+    dev: bool,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    dev_optional: bool,
+    #[serde(default)]
+    peer: bool,
+    #[serde(default)]
+    link: bool,
+    #[serde(default)]
+    dependencies: HashMap<String, String>,
+    #[serde(default)]
+    optional_dependencies: HashMap<String, String>,
+    #[serde(default)]
+    peer_dependencies: HashMap<String, String>,
+    #[serde(default)]
+    dev_dependencies: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageLockV1Entry {
+    version: String,
+    #[serde(default)]
+    dev: bool,
+    #[serde(default)]
+    optional: bool,
+    #[serde(default)]
+    requires: HashMap<String, String>,
+    #[serde(default)]
+    dependencies: HashMap<String, PackageLockV1Entry>,
+}
+
+impl PackageLockEntry {
+    fn locked_kind(&self) -> DependencyKind {
+        if self.dev {
+            DependencyKind::Dev
+        } else if self.peer {
+            DependencyKind::Peer
+        } else if self.optional || self.dev_optional {
+            DependencyKind::Optional
+        } else {
+            DependencyKind::Normal
+        }
+    }
+
+    fn required_names(&self) -> impl Iterator<Item = &String> {
+        self.dependencies
+            .keys()
+            .chain(self.optional_dependencies.keys())
+            .chain(self.peer_dependencies.keys())
+    }
+
+    /// The kind of every dependency the package declares, going by the
+    /// section it is declared in.
+    fn declared_kinds(&self) -> impl Iterator<Item = (&String, DependencyKind)> {
+        [
+            (&self.dependencies, DependencyKind::Normal),
+            (&self.optional_dependencies, DependencyKind::Optional),
+            (&self.peer_dependencies, DependencyKind::Peer),
+            (&self.dev_dependencies, DependencyKind::Dev),
+        ]
+        .into_iter()
+        .flat_map(|(section, kind)| section.keys().map(move |name| (name, kind)))
+    }
+}
+
+pub async fn collect_dependencies(path: &str) -> Result<CollectorOutput> {
     debug!("Scanning directory for package-lock.json: {}", path);
 
     let package_lock_path = format!("{}/package-lock.json", path);
-    let contents = match read_to_string(&package_lock_path) {
-        Ok(c) => c,
-        Err(_e) => {
-            debug!("Could not read package-lock.json at {}", package_lock_path);
-            return Ok(Vec::new());
-        }
-    };
+    let contents = read_to_string(&package_lock_path)
+        .with_context(|| format!("Failed to read package-lock.json for {}", path))?;
+
+    let lockfile: PackageLockFile = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse package-lock.json for {}", path))?;
 
-    let dependencies_result = match parse_dependencies(&contents) {
-        Ok(deps) => deps,
-        Err(e) => {
-            debug!("Failed to parse package-lock.json for {}: {}", path, e);
-            return Ok(Vec::new());
+    let mut packages = match lockfile.packages {
+        Some(packages) => packages,
+        None => {
+            let mut packages = HashMap::new();
+            flatten_v1_dependencies("", lockfile.dependencies, &mut packages);
+            packages
         }
     };
 
-    debug!(
-        "Found {} packages in package-lock.json",
-        dependencies_result.len()
-    );
+    debug!("Found {} packages in package-lock.json", packages.len());
 
-    let mut dependencies_map: HashMap<(String, String), CollectorDependency> = HashMap::new();
+    // v1 lockfiles have no root entry, the package.json names the direct
+    // dependencies instead.
+    let declared_kinds = declared_dependency_kinds(path);
+    let root = packages.remove("").unwrap_or_else(|| PackageLockEntry {
+        dependencies: declared_kinds
+            .keys()
+            .map(|name| (name.clone(), String::new()))
+            .collect(),
+        ..Default::default()
+    });
+    let direct_keys: Vec<String> = root
+        .required_names()
+        .chain(root.dev_dependencies.keys())
+        .filter_map(|name| resolve_package(&packages, "", name))
+        .collect();
 
-    for dep in dependencies_result {
-        let name = dep.name;
-        let version = dep.version;
-        let key = (name.clone(), version.clone());
-        dependencies_map.entry(key).or_insert_with(|| {
-            CollectorDependency::new(name, version, ProjectLanguage::Javascript)
-        });
+    let mut dependencies = Vec::new();
+    for (key, entry) in &packages {
+        let Some(name) = package_name(key) else {
+            continue;
+        };
+        let Some(version) = entry.version.clone().filter(|_| !entry.link) else {
+            continue;
+        };
+        let is_direct = direct_keys.contains(key);
+        let declared = declared_kinds.get(name).copied().filter(|_| is_direct);
+        let kind = resolve_kind(Some(entry.locked_kind()), declared);
+        let depends_on = entry
+            .required_names()
+            .filter_map(|child| resolve_package(&packages, key, child))
+            .filter_map(|child_key| {
+                let child = packages.get(&child_key)?;
+                Some((
+                    package_name(&child_key)?.to_string(),
+                    child.version.clone()?,
+                ))
+            })
+            .collect();
+        let source_path = installed_package_at(&Path::new(path).join(key), name, &version);
+        dependencies.push(CollectorDependency::new(
+            name.to_string(),
+            version,
+            ProjectLanguage::Javascript,
+            source_path,
+            kind,
+            is_direct,
+            depends_on,
+        ));
     }
 
-    let dependencies: Vec<CollectorDependency> = dependencies_map.into_values().collect();
+    let dependencies = dedupe_dependencies(dependencies);
 
     debug!(
         "Found {} unique JavaScript dependencies",
         dependencies.len()
     );
 
-    Ok(dependencies)
+    // Workspace packages are the keys outside node_modules, their lockfile
+    // entries list what each of them declares.
+    let members = packages
+        .iter()
+        .filter(|(key, entry)| package_name(key).is_none() && !entry.link)
+        .map(|(key, entry)| {
+            let mut direct_kinds: HashMap<(String, String), DependencyKind> = HashMap::new();
+            for (name, kind) in entry.declared_kinds() {
+                let Some(version) = resolve_package(&packages, key, name)
+                    .and_then(|child| packages.get(&child)?.version.clone())
+                else {
+                    continue;
+                };
+                direct_kinds
+                    .entry((name.clone(), version))
+                    .and_modify(|existing| *existing = existing.strongest(kind))
+                    .or_insert(kind);
+            }
+            let mut member_dependencies = member_dependencies(&dependencies, &direct_kinds);
+            inherit_kinds(&mut member_dependencies);
+            let member_path = Path::new(path).join(key);
+            WorkspaceMember {
+                name: entry
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| key.rsplit('/').next().unwrap_or(key.as_str()).to_string()),
+                path: member_path.to_string_lossy().into_owned(),
+                dependencies: member_dependencies,
+            }
+        })
+        .collect();
+
+    Ok(CollectorOutput {
+        dependencies,
+        members,
+    })
+}
+
+/// Rewrites the nested v1 tree into the path keyed layout of later versions.
+fn flatten_v1_dependencies(
+    parent: &str,
+    dependencies: HashMap<String, PackageLockV1Entry>,
+    packages: &mut HashMap<String, PackageLockEntry>,
+) {
+    for (name, dependency) in dependencies {
+        let key = if parent.is_empty() {
+            format!("{}{}", NODE_MODULES, name)
+        } else {
+            format!("{}/{}{}", parent, NODE_MODULES, name)
+        };
+        flatten_v1_dependencies(&key, dependency.dependencies, packages);
+        packages.insert(
+            key,
+            PackageLockEntry {
+                version: Some(dependency.version),
+                dev: dependency.dev,
+                optional: dependency.optional,
+                dependencies: dependency.requires,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+/// The package name is whatever follows the last `node_modules/` in the key.
+/// Keys without one are workspace packages, not installed dependencies.
+fn package_name(key: &str) -> Option<&str> {
+    key.rfind(NODE_MODULES)
+        .map(|index| &key[index + NODE_MODULES.len()..])
+}
+
+/// Follows Node's module resolution: look in the package's own node_modules
+/// and then in each enclosing one up to the project root.
+fn resolve_package(
+    packages: &HashMap<String, PackageLockEntry>,
+    from: &str,
+    name: &str,
+) -> Option<String> {
+    let mut base = from;
+    loop {
+        let candidate = if base.is_empty() {
+            format!("{}{}", NODE_MODULES, name)
+        } else {
+            format!("{}/{}{}", base, NODE_MODULES, name)
+        };
+        if packages.contains_key(&candidate) {
+            return Some(candidate);
+        }
+        if base.is_empty() {
+            return None;
+        }
+        base = match base.rfind(NODE_MODULES) {
+            Some(index) => base[..index].trim_end_matches('/'),
+            None => "",
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packages(keys: &[&str]) -> HashMap<String, PackageLockEntry> {
+        keys.iter()
+            .map(|key| (key.to_string(), PackageLockEntry::default()))
+            .collect()
+    }
+
+    #[test]
+    fn resolves_packages_like_node() {
+        let packages = packages(&[
+            "node_modules/a",
+            "node_modules/b",
+            "node_modules/a/node_modules/b",
+            "node_modules/a/node_modules/b/node_modules/c",
+            "node_modules/@scope/d",
+            "packages/app",
+            "packages/app/node_modules/a",
+        ]);
+        let resolve = |from: &str, name: &str| resolve_package(&packages, from, name);
+
+        assert_eq!(resolve("", "a").as_deref(), Some("node_modules/a"));
+        assert_eq!(
+            resolve("node_modules/a", "b").as_deref(),
+            Some("node_modules/a/node_modules/b")
+        );
+        // A nested package falls back to the enclosing node_modules.
+        assert_eq!(
+            resolve("node_modules/a/node_modules/b/node_modules/c", "a").as_deref(),
+            Some("node_modules/a")
+        );
+        assert_eq!(
+            resolve("node_modules/a/node_modules/b", "@scope/d").as_deref(),
+            Some("node_modules/@scope/d")
+        );
+        // Workspace packages resolve from their own node_modules first.
+        assert_eq!(
+            resolve("packages/app", "a").as_deref(),
+            Some("packages/app/node_modules/a")
+        );
+        assert_eq!(
+            resolve("packages/app", "b").as_deref(),
+            Some("node_modules/b")
+        );
+        assert_eq!(resolve("node_modules/a", "missing"), None);
+    }
+
+    #[test]
+    fn flattens_the_v1_tree() {
+        let lockfile: PackageLockFile = serde_json::from_str(
+            r#"{
+                "dependencies": {
+                    "a": {
+                        "version": "1.0.0",
+                        "requires": { "b": "^2.0.0" },
+                        "dependencies": {
+                            "b": {
+                                "version": "2.0.0",
+                                "optional": true,
+                                "dependencies": {
+                                    "c": { "version": "3.0.0" }
+                                }
+                            }
+                        }
+                    },
+                    "b": { "version": "1.0.0", "dev": true }
+                }
+            }"#,
+        )
+        .unwrap();
+        let mut packages = HashMap::new();
+        flatten_v1_dependencies("", lockfile.dependencies, &mut packages);
+
+        let mut keys: Vec<&str> = packages.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            [
+                "node_modules/a",
+                "node_modules/a/node_modules/b",
+                "node_modules/a/node_modules/b/node_modules/c",
+                "node_modules/b",
+            ]
+        );
+
+        let a = &packages["node_modules/a"];
+        assert_eq!(a.version.as_deref(), Some("1.0.0"));
+        assert!(a.dependencies.contains_key("b"));
+        assert_eq!(
+            packages["node_modules/a/node_modules/b"].locked_kind(),
+            DependencyKind::Optional
+        );
+        assert_eq!(
+            packages["node_modules/b"].locked_kind(),
+            DependencyKind::Dev
+        );
+        assert_eq!(
+            resolve_package(&packages, "node_modules/a", "b").as_deref(),
+            Some("node_modules/a/node_modules/b")
+        );
+    }
 }
@@ -0,0 +1,397 @@
+use std::{collections::HashMap, fs::read_to_string, path::Path};
+
+use anyhow::{Context, Result};
+use log::debug;
+use yaml_rust2::{Yaml, YamlLoader};
+
+use crate::{
+    collectors::{
+        CollectorDependency, CollectorOutput, WorkspaceMember, dedupe_dependencies, inherit_kinds,
+        javascript::{
+            declared_dependency_kinds, installed_package_path, manifest_name, resolve_kind,
+        },
+        member_dependencies,
+    },
+    models::{DependencyKind, ProjectLanguage},
+};
+
+const IMPORTER_DEPENDENCY_SECTIONS: &[(&str, DependencyKind)] = &[
+    ("dependencies", DependencyKind::Normal),
+    ("devDependencies", DependencyKind::Dev),
+    ("optionalDependencies", DependencyKind::Optional),
+];
+
+pub async fn collect_dependencies(path: &str) -> Result<CollectorOutput> {
+    debug!("Scanning directory for pnpm-lock.yaml: {}", path);
+
+    let pnpm_lock_path = format!("{}/pnpm-lock.yaml", path);
+    let contents = read_to_string(&pnpm_lock_path)
+        .with_context(|| format!("Failed to read pnpm-lock.yaml for {}", path))?;
+
+    let documents = YamlLoader::load_from_str(&contents)
+        .with_context(|| format!("Failed to parse pnpm-lock.yaml for {}", path))?;
+    let Some(document) = documents.first() else {
+        debug!("Empty pnpm-lock.yaml for {}", path);
+        return Ok(CollectorOutput::default());
+    };
+    let Some(packages) = document["packages"].as_hash() else {
+        debug!("No packages found in pnpm-lock.yaml for {}", path);
+        return Ok(CollectorOutput::default());
+    };
+
+    debug!("Found {} packages in pnpm-lock.yaml", packages.len());
+
+    // Lockfile v5 keeps the root project's dependencies at the top level,
+    // later versions nest them under `importers`.
+    let importer = match &document["importers"]["."] {
+        Yaml::BadValue => document,
+        importer => importer,
+    };
+    let direct_kinds = importer_direct_kinds(importer, path);
+
+    // v9 moved each package's dependencies from `packages` to `snapshots`.
+    let snapshots = document["snapshots"].as_hash();
+    let mut has_kind_flags = false;
+    let mut dependencies = Vec::new();
+    for (key, package) in packages.iter().chain(snapshots.into_iter().flatten()) {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        let Some((name, version)) = parse_package_key(key) else {
+            debug!("Skipping unrecognised pnpm package key {}", key);
+            continue;
+        };
+        let locked = locked_kind(package);
+        has_kind_flags |= locked.is_some();
+        let declared = direct_kinds.get(&(name.clone(), version.clone())).copied();
+        let kind = resolve_kind(locked, declared);
+        let depends_on = ["dependencies", "optionalDependencies"]
+            .iter()
+            .flat_map(|section| dependency_references(&package[*section]))
+            .filter_map(|(name, reference)| parse_reference(&name, &reference))
+            .collect();
+        let source_path = installed_package_path(path, &name, &version);
+        dependencies.push(CollectorDependency::new(
+            name,
+            version,
+            ProjectLanguage::Javascript,
+            source_path,
+            kind,
+            declared.is_some(),
+            depends_on,
+        ));
+    }
+
+    let mut dependencies = dedupe_dependencies(dependencies);
+    if !has_kind_flags {
+        inherit_kinds(&mut dependencies);
+    }
+
+    debug!(
+        "Found {} unique JavaScript dependencies",
+        dependencies.len()
+    );
+
+    // Every other importer is a workspace package, keyed by its path relative
+    // to the lockfile.
+    let members = document["importers"]
+        .as_hash()
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, importer)| {
+            let key = key.as_str().filter(|key| *key != ".")?;
+            let member_path = Path::new(path).join(key).to_str()?.to_string();
+            let direct_kinds = importer_direct_kinds(importer, &member_path);
+            let mut member_dependencies = member_dependencies(&dependencies, &direct_kinds);
+            if !has_kind_flags {
+                inherit_kinds(&mut member_dependencies);
+            }
+            Some(WorkspaceMember {
+                name: manifest_name(&member_path)
+                    .unwrap_or_else(|| key.rsplit('/').next().unwrap_or(key).to_string()),
+                path: member_path,
+                dependencies: member_dependencies,
+            })
+        })
+        .collect();
+
+    Ok(CollectorOutput {
+        dependencies,
+        members,
+    })
+}
+
+/// Kinds of the packages an importer depends on directly, keyed by the
+/// locked `(name, version)` they resolved to. `path` is the directory of the
+/// importer's package.json.
+fn importer_direct_kinds(importer: &Yaml, path: &str) -> HashMap<(String, String), DependencyKind> {
+    let package_json_kinds = declared_dependency_kinds(path);
+    let mut direct_kinds: HashMap<(String, String), DependencyKind> = HashMap::new();
+    for (section, kind) in IMPORTER_DEPENDENCY_SECTIONS {
+        for (name, reference) in dependency_references(&importer[*section]) {
+            let Some(key) = parse_reference(&name, &reference) else {
+                continue;
+            };
+            let kind = resolve_kind(Some(*kind), package_json_kinds.get(&name).copied());
+            direct_kinds
+                .entry(key)
+                .and_modify(|existing| *existing = existing.strongest(kind))
+                .or_insert(kind);
+        }
+    }
+    direct_kinds
+}
+
+/// Lockfile versions before 9 flag every package with `dev` and `optional`,
+/// v9 dropped these flags.
+fn locked_kind(package: &Yaml) -> Option<DependencyKind> {
+    let dev = package["dev"].as_bool();
+    let optional = package["optional"].as_bool().unwrap_or(false);
+    match dev {
+        Some(true) => Some(DependencyKind::Dev),
+        Some(false) if optional => Some(DependencyKind::Optional),
+        Some(false) => Some(DependencyKind::Normal),
+        None if optional => Some(DependencyKind::Optional),
+        None => None,
+    }
+}
+
+/// `(name, reference)` pairs of a dependency map. Importers in lockfile v6
+/// and later wrap the reference as `{ specifier, version }`.
+fn dependency_references(section: &Yaml) -> Vec<(String, String)> {
+    let Some(section) = section.as_hash() else {
+        return Vec::new();
+    };
+    section
+        .iter()
+        .filter_map(|(name, value)| {
+            let reference = value.as_str().or_else(|| value["version"].as_str())?;
+            Some((name.as_str()?.to_string(), reference.to_string()))
+        })
+        .collect()
+}
+
+/// Resolves a reference such as `1.2.3`, `1.2.3(react@18.2.0)` or
+/// `1.2.3_react@18.2.0` to the locked package. Aliased dependencies point at
+/// another package key instead, local `link:` and `file:` references are
+/// not dependencies.
+fn parse_reference(name: &str, reference: &str) -> Option<(String, String)> {
+    if reference.starts_with(|c: char| c.is_ascii_digit()) {
+        let version = reference.split(['(', '_']).next()?;
+        return Some((name.to_string(), version.to_string()));
+    }
+    if reference.starts_with("link:") || reference.starts_with("file:") {
+        return None;
+    }
+    parse_package_key(reference)
+}
+
+/// Handles the package key formats of every lockfile version:
+/// `/name/1.2.3_peer` (v5), `/name@1.2.3(peer)` (v6) and `name@1.2.3` (v9).
+fn parse_package_key(key: &str) -> Option<(String, String)> {
+    let key = key.trim_start_matches('/');
+    let key = key.split('(').next()?;
+
+    // In v5 an `@` can only come from a peer suffix such as `_react@18.2.0`,
+    // after the slash that separates name and version.
+    let scope_offset = usize::from(key.starts_with('@'));
+    let (name, version) = match key[scope_offset..].find('@').map(|i| i + scope_offset) {
+        Some(index) if key[..index].matches('/').count() == scope_offset => key.split_at(index),
+        _ => {
+            let (name, version) = key.rsplit_once('/')?;
+            (name, version.split('_').next()?)
+        }
+    };
+    let version = version.trim_start_matches('@');
+
+    if name.is_empty()
+        || !version.starts_with(|c: char| c.is_ascii_digit())
+        || version.contains([':', '/'])
+    {
+        return None;
+    }
+    Some((name.to_string(), version.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V5_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pnpm_v5");
+    const V6_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pnpm_v6");
+    const V9_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/pnpm_v9");
+
+    fn key(name: &str, version: &str) -> Option<(String, String)> {
+        Some((name.to_string(), version.to_string()))
+    }
+
+    fn find<'a>(dependencies: &'a [CollectorDependency], name: &str) -> &'a CollectorDependency {
+        dependencies
+            .iter()
+            .find(|d| d.name == name)
+            .unwrap_or_else(|| panic!("{} was not collected", name))
+    }
+
+    fn names(dependencies: &[CollectorDependency]) -> Vec<&str> {
+        let mut names: Vec<&str> = dependencies.iter().map(|d| d.name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn parses_package_keys_of_every_lockfile_version() {
+        assert_eq!(parse_package_key("/react/18.2.0"), key("react", "18.2.0"));
+        assert_eq!(
+            parse_package_key("/@types/react/18.2.0"),
+            key("@types/react", "18.2.0")
+        );
+        assert_eq!(parse_package_key("/react@18.2.0"), key("react", "18.2.0"));
+        assert_eq!(
+            parse_package_key("/@types/react@18.2.0"),
+            key("@types/react", "18.2.0")
+        );
+        assert_eq!(parse_package_key("react@18.2.0"), key("react", "18.2.0"));
+        assert_eq!(
+            parse_package_key("@types/react@18.2.0"),
+            key("@types/react", "18.2.0")
+        );
+    }
+
+    #[test]
+    fn strips_peer_suffixes_from_package_keys() {
+        assert_eq!(
+            parse_package_key("/react-dom/18.2.0_react@18.2.0"),
+            key("react-dom", "18.2.0")
+        );
+        assert_eq!(
+            parse_package_key("/@testing-library/react/14.0.0_biqbaboplfbrettd7655fr4n2y"),
+            key("@testing-library/react", "14.0.0")
+        );
+        assert_eq!(
+            parse_package_key("/react-dom@18.2.0(react@18.2.0)"),
+            key("react-dom", "18.2.0")
+        );
+        assert_eq!(
+            parse_package_key("@testing-library/react@14.0.0(@types/react@18.2.0)(react@18.2.0)"),
+            key("@testing-library/react", "14.0.0")
+        );
+    }
+
+    #[test]
+    fn local_references_are_not_dependencies() {
+        assert_eq!(parse_reference("shared", "link:../shared"), None);
+        assert_eq!(parse_reference("vendored", "file:vendor/pkg.tgz"), None);
+        assert_eq!(parse_package_key("shared@workspace:packages/shared"), None);
+        assert_eq!(
+            parse_reference("react-dom", "18.2.0_react@18.2.0"),
+            key("react-dom", "18.2.0")
+        );
+        assert_eq!(
+            parse_reference("react-dom", "18.2.0(react@18.2.0)"),
+            key("react-dom", "18.2.0")
+        );
+        // An alias resolves to the package it points at.
+        assert_eq!(
+            parse_reference("old-react", "/react@17.0.2"),
+            key("react", "17.0.2")
+        );
+    }
+
+    #[tokio::test]
+    async fn v5_lockfile_uses_top_level_importer_and_dev_flags() {
+        let output = collect_dependencies(V5_FIXTURE).await.unwrap();
+        let dependencies = output.dependencies;
+
+        assert_eq!(
+            names(&dependencies),
+            [
+                "@types/react",
+                "js-tokens",
+                "loose-envify",
+                "react",
+                "react-dom"
+            ]
+        );
+        let dom = find(&dependencies, "react-dom");
+        assert_eq!(dom.version, "18.2.0");
+        assert!(dom.is_direct);
+        assert!(
+            dom.depends_on
+                .contains(&("react".to_string(), "18.2.0".to_string()))
+        );
+
+        let types = find(&dependencies, "@types/react");
+        assert!(types.is_direct);
+        assert_eq!(types.kind, DependencyKind::Dev);
+        assert!(!find(&dependencies, "js-tokens").is_direct);
+        assert!(output.members.is_empty());
+    }
+
+    #[tokio::test]
+    async fn v6_lockfile_reports_importers_as_members() {
+        let output = collect_dependencies(V6_FIXTURE).await.unwrap();
+        let dependencies = output.dependencies;
+
+        assert_eq!(
+            names(&dependencies),
+            [
+                "@types/react",
+                "js-tokens",
+                "loose-envify",
+                "react",
+                "react-dom"
+            ]
+        );
+        assert!(find(&dependencies, "react-dom").is_direct);
+        assert_eq!(
+            find(&dependencies, "@types/react").kind,
+            DependencyKind::Dev
+        );
+
+        let [member] = output.members.as_slice() else {
+            panic!("expected one workspace member, got {:?}", output.members);
+        };
+        assert_eq!(member.name, "@app/ui");
+        assert!(member.path.ends_with("pnpm_v6/packages/ui"));
+        // The `link:` dependency on another workspace package is left out.
+        let direct: Vec<&str> = member
+            .dependencies
+            .iter()
+            .filter(|d| d.is_direct)
+            .map(|d| d.name.as_str())
+            .collect();
+        assert_eq!(direct, ["react"]);
+    }
+
+    #[tokio::test]
+    async fn v9_lockfile_reads_snapshots_and_inherits_kinds() {
+        let output = collect_dependencies(V9_FIXTURE).await.unwrap();
+        let dependencies = output.dependencies;
+
+        assert_eq!(
+            names(&dependencies),
+            [
+                "js-tokens",
+                "loose-envify",
+                "react",
+                "react-dom",
+                "typescript"
+            ]
+        );
+        let dom = find(&dependencies, "react-dom");
+        assert!(dom.is_direct);
+        assert_eq!(dom.kind, DependencyKind::Normal);
+        assert!(
+            dom.depends_on
+                .contains(&("loose-envify".to_string(), "1.4.0".to_string()))
+        );
+
+        let tokens = find(&dependencies, "js-tokens");
+        assert!(!tokens.is_direct);
+        assert_eq!(tokens.kind, DependencyKind::Normal);
+
+        let typescript = find(&dependencies, "typescript");
+        assert!(typescript.is_direct);
+        assert_eq!(typescript.kind, DependencyKind::Dev);
+    }
+}
@@ -0,0 +1,689 @@
+use std::{
+    collections::HashMap,
+    fs::{read_dir, read_to_string},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use log::debug;
+use serde::Deserialize;
+
+use crate::{
+    collectors::{
+        Collector, CollectorDependency, CollectorOutput, dedupe_dependencies, inherit_kinds,
+    },
+    models::{DependencyKind, ProjectLanguage},
+};
+
+const LOCKFILES: &[&str] = &["uv.lock", "poetry.lock"];
+
+#[derive(Debug, Deserialize)]
+struct PythonLockFile {
+    #[serde(default)]
+    package: Vec<PythonLockPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PythonLockPackage {
+    name: String,
+    version: Option<String>,
+    source: Option<PythonLockSource>,
+    /// poetry.lock before 1.2 marks packages only needed for development.
+    category: Option<String>,
+    /// poetry.lock from 2.0 lists the dependency groups that need a package.
+    groups: Option<Vec<String>>,
+    #[serde(default)]
+    optional: bool,
+    dependencies: Option<PythonLockDependencies>,
+    /// uv.lock lists the dev groups and extras of the project itself.
+    #[serde(rename = "dev-dependencies", default)]
+    dev_dependencies: HashMap<String, Vec<PythonDependencyRef>>,
+    #[serde(rename = "optional-dependencies", default)]
+    optional_dependencies: HashMap<String, Vec<PythonDependencyRef>>,
+}
+
+/// uv.lock writes `dependencies = [{ name = "..." }]` while poetry.lock has a
+/// `[package.dependencies]` table keyed by name.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PythonLockDependencies {
+    List(Vec<PythonDependencyRef>),
+    Table(HashMap<String, toml::Value>),
+}
+
+#[derive(Debug, Deserialize)]
+struct PythonDependencyRef {
+    name: String,
+    /// Only written by uv when more than one version of the package is locked.
+    version: Option<String>,
+}
+
+/// The parts of pyproject.toml that declare direct dependencies.
+#[derive(Debug, Default, Deserialize)]
+struct PyProject {
+    #[serde(default)]
+    project: PyProjectMetadata,
+    #[serde(rename = "dependency-groups", default)]
+    dependency_groups: HashMap<String, Vec<toml::Value>>,
+    #[serde(default)]
+    tool: PyProjectTools,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PyProjectMetadata {
+    #[serde(default)]
+    dependencies: Vec<String>,
+    #[serde(rename = "optional-dependencies", default)]
+    optional_dependencies: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PyProjectTools {
+    #[serde(default)]
+    poetry: PoetryTool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PoetryTool {
+    #[serde(default)]
+    dependencies: HashMap<String, toml::Value>,
+    #[serde(rename = "dev-dependencies", default)]
+    dev_dependencies: HashMap<String, toml::Value>,
+    #[serde(default)]
+    group: HashMap<String, PoetryGroup>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PoetryGroup {
+    #[serde(default)]
+    dependencies: HashMap<String, toml::Value>,
+}
+
+impl PythonLockPackage {
+    /// `(name, version)` of each dependency, the version is only known when
+    /// the lockfile spells it out.
+    fn dependency_refs(&self) -> Vec<(String, Option<String>)> {
+        match &self.dependencies {
+            Some(PythonLockDependencies::List(dependencies)) => dependencies
+                .iter()
+                .map(|d| (normalize_name(&d.name), d.version.clone()))
+                .collect(),
+            Some(PythonLockDependencies::Table(dependencies)) => dependencies
+                .keys()
+                .map(|name| (normalize_name(name), None))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn locked_kind(&self) -> Option<DependencyKind> {
+        if self.category.as_deref() == Some("dev")
+            || self
+                .groups
+                .as_ref()
+                .is_some_and(|groups| !groups.iter().any(|g| g == "main"))
+        {
+            Some(DependencyKind::Dev)
+        } else if self.optional {
+            Some(DependencyKind::Optional)
+        } else {
+            None
+        }
+    }
+}
+
+/// Covers both `[package.source]` in poetry.lock and `source = { ... }` in uv.lock.
+#[derive(Debug, Deserialize)]
+struct PythonLockSource {
+    #[serde(rename = "type")]
+    source_type: Option<String>,
+    editable: Option<String>,
+    r#virtual: Option<String>,
+    /// uv writes packages installed from a local directory, wheel or sdist
+    /// without `editable` as `directory` or `path`.
+    directory: Option<String>,
+    path: Option<String>,
+}
+
+impl PythonLockSource {
+    fn is_local_project(&self) -> bool {
+        self.editable.is_some()
+            || self.r#virtual.is_some()
+            || self.directory.is_some()
+            || self.path.is_some()
+            || matches!(self.source_type.as_deref(), Some("directory" | "file"))
+    }
+}
+
+pub struct PythonCollector;
+
+#[async_trait]
+impl Collector for PythonCollector {
+    fn language(&self) -> ProjectLanguage {
+        ProjectLanguage::Python
+    }
+
+    fn applies_to(&self, path: &Path) -> bool {
+        LOCKFILES
+            .iter()
+            .any(|lockfile| path.join(lockfile).is_file())
+            || !requirements_files(path).is_empty()
+    }
+
+    fn watches(&self, file_name: &str) -> bool {
+        file_name == "pyproject.toml"
+            || LOCKFILES.contains(&file_name)
+            || is_requirements_file(file_name)
+    }
+
+    async fn collect(&self, path: &str) -> Result<CollectorOutput> {
+        collect_dependencies(path).await.map(CollectorOutput::from)
+    }
+}
+
+pub async fn collect_dependencies(path: &str) -> Result<Vec<CollectorDependency>> {
+    debug!("Scanning directory for Python requirements: {}", path);
+
+    let declared_kinds = pyproject_dependency_kinds(path);
+    let mut dependencies = Vec::new();
+    for lockfile in LOCKFILES {
+        dependencies.extend(collect_lockfile(path, lockfile, &declared_kinds)?);
+    }
+    for (name, version, kind) in collect_requirements_files(path) {
+        dependencies.push(CollectorDependency::new(
+            normalize_name(&name),
+            version,
+            ProjectLanguage::Python,
+            None,
+            kind,
+            true,
+            Vec::new(),
+        ));
+    }
+
+    let dependencies = dedupe_dependencies(dependencies);
+
+    debug!("Found {} unique Python dependencies", dependencies.len());
+
+    Ok(dependencies)
+}
+
+fn collect_lockfile(
+    path: &str,
+    file_name: &str,
+    pyproject_kinds: &HashMap<String, DependencyKind>,
+) -> Result<Vec<CollectorDependency>> {
+    let lockfile_path = format!("{}/{}", path, file_name);
+    let contents = match read_to_string(&lockfile_path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {} for {}", file_name, path));
+        }
+    };
+
+    let lockfile: PythonLockFile = toml::from_str(&contents)
+        .with_context(|| format!("Failed to parse {} for {}", file_name, path))?;
+
+    debug!("Found {} packages in {}", lockfile.package.len(), file_name);
+
+    let (local, packages): (Vec<_>, Vec<_>) = lockfile
+        .package
+        .into_iter()
+        .partition(|p| p.source.as_ref().is_some_and(|s| s.is_local_project()));
+    let mut declared_kinds = declared_dependency_kinds(&local);
+    for (name, kind) in pyproject_kinds {
+        declared_kinds
+            .entry(name.clone())
+            .and_modify(|existing| *existing = existing.strongest(*kind))
+            .or_insert(*kind);
+    }
+    let has_kind_flags = packages
+        .iter()
+        .any(|p| p.category.is_some() || p.groups.is_some());
+
+    let mut locked_versions: HashMap<String, String> = HashMap::new();
+    for package in &packages {
+        if let Some(version) = &package.version {
+            locked_versions
+                .entry(normalize_name(&package.name))
+                .or_insert_with(|| version.clone());
+        }
+    }
+
+    let mut dependencies: Vec<CollectorDependency> = packages
+        .into_iter()
+        .filter_map(|p| {
+            let name = normalize_name(&p.name);
+            let declared = declared_kinds.get(&name).copied();
+            let kind = p
+                .locked_kind()
+                .or(declared)
+                .unwrap_or(DependencyKind::Normal);
+            let depends_on = p
+                .dependency_refs()
+                .into_iter()
+                .filter_map(|(name, version)| {
+                    let version = version.or_else(|| locked_versions.get(&name).cloned())?;
+                    Some((name, version))
+                })
+                .collect();
+            let version = p.version?;
+            Some(CollectorDependency::new(
+                name,
+                version,
+                ProjectLanguage::Python,
+                None,
+                kind,
+                declared.is_some(),
+                depends_on,
+            ))
+        })
+        .collect();
+
+    if !has_kind_flags {
+        inherit_kinds(&mut dependencies);
+    }
+    Ok(dependencies)
+}
+
+/// uv.lock does not flag packages, but the local project entries list which
+/// of their direct dependencies belong to a dev group or an extra.
+fn declared_dependency_kinds(local: &[PythonLockPackage]) -> HashMap<String, DependencyKind> {
+    let mut kinds: HashMap<String, DependencyKind> = HashMap::new();
+    let mut declare = |name: &str, kind: DependencyKind| {
+        kinds
+            .entry(normalize_name(name))
+            .and_modify(|existing| *existing = existing.strongest(kind))
+            .or_insert(kind);
+    };
+    for package in local {
+        for (name, _) in package.dependency_refs() {
+            declare(&name, DependencyKind::Normal);
+        }
+        for dependency in package.dev_dependencies.values().flatten() {
+            declare(&dependency.name, DependencyKind::Dev);
+        }
+        for dependency in package.optional_dependencies.values().flatten() {
+            declare(&dependency.name, DependencyKind::Optional);
+        }
+    }
+    kinds
+}
+
+/// Direct dependencies declared in pyproject.toml, either in the standard
+/// `[project]` and `[dependency-groups]` tables or in `[tool.poetry]`.
+fn pyproject_dependency_kinds(path: &str) -> HashMap<String, DependencyKind> {
+    let mut kinds: HashMap<String, DependencyKind> = HashMap::new();
+    let Some(pyproject) = read_to_string(Path::new(path).join("pyproject.toml"))
+        .ok()
+        .and_then(|contents| toml::from_str::<PyProject>(&contents).ok())
+    else {
+        return kinds;
+    };
+
+    let mut declare = |name: &str, kind: DependencyKind| {
+        if name.is_empty() || name == "python" {
+            return;
+        }
+        kinds
+            .entry(normalize_name(name))
+            .and_modify(|existing| *existing = existing.strongest(kind))
+            .or_insert(kind);
+    };
+    for requirement in &pyproject.project.dependencies {
+        declare(requirement_name(requirement), DependencyKind::Normal);
+    }
+    for requirement in pyproject.project.optional_dependencies.values().flatten() {
+        declare(requirement_name(requirement), DependencyKind::Optional);
+    }
+    // Groups may also hold `{ include-group = "..." }` tables, which name no package.
+    for requirement in pyproject
+        .dependency_groups
+        .values()
+        .flatten()
+        .filter_map(toml::Value::as_str)
+    {
+        declare(requirement_name(requirement), DependencyKind::Dev);
+    }
+    let poetry = &pyproject.tool.poetry;
+    for name in poetry.dependencies.keys() {
+        declare(name, DependencyKind::Normal);
+    }
+    for name in poetry
+        .dev_dependencies
+        .keys()
+        .chain(poetry.group.values().flat_map(|g| g.dependencies.keys()))
+    {
+        declare(name, DependencyKind::Dev);
+    }
+    kinds
+}
+
+/// The distribution name at the start of a PEP 508 requirement such as
+/// `requests[socks]>=2.31; python_version >= "3.8"`.
+fn requirement_name(requirement: &str) -> &str {
+    let requirement = requirement.trim();
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    &requirement[..end]
+}
+
+/// Reads every `requirements*.txt` at the project root and keeps pinned
+/// (`==` or `===`) requirements only.
+fn collect_requirements_files(path: &str) -> Vec<(String, String, DependencyKind)> {
+    let mut requirements = Vec::new();
+    for file in requirements_files(Path::new(path)) {
+        let Ok(contents) = read_to_string(&file) else {
+            continue;
+        };
+        let kind = file
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map_or(DependencyKind::Normal, requirements_file_kind);
+        let pinned = parse_requirements(&contents);
+        debug!(
+            "Found {} pinned requirements in {}",
+            pinned.len(),
+            file.display()
+        );
+        requirements.extend(
+            pinned
+                .into_iter()
+                .map(|(name, version)| (name, version, kind)),
+        );
+    }
+    requirements
+}
+
+fn requirements_files(path: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = read_dir(path) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(is_requirements_file)
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+fn is_requirements_file(file_name: &str) -> bool {
+    file_name.starts_with("requirements") && file_name.ends_with(".txt")
+}
+
+/// Files such as `requirements-dev.txt` or `requirements_test.txt` hold
+/// development dependencies. Only whole words count, `requirements-latest.txt`
+/// is not a test file.
+fn requirements_file_kind(file_name: &str) -> DependencyKind {
+    let stem = file_name.strip_suffix(".txt").unwrap_or(file_name);
+    let is_dev = stem.split(['-', '_', '.']).any(|word| {
+        matches!(
+            word.to_ascii_lowercase().as_str(),
+            "dev" | "develop" | "development" | "test" | "tests" | "testing"
+        )
+    });
+    if is_dev {
+        DependencyKind::Dev
+    } else {
+        DependencyKind::Normal
+    }
+}
+
+fn parse_requirements(contents: &str) -> Vec<(String, String)> {
+    let joined = contents.replace("\\\r\n", " ").replace("\\\n", " ");
+    joined
+        .lines()
+        .filter_map(|line| {
+            let line = match line.find(" #") {
+                Some(index) => &line[..index],
+                None => line,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+                return None;
+            }
+            let requirement = line.split(';').next()?.split(" --").next()?.trim();
+            let (name, version) = requirement
+                .split_once("===")
+                .or_else(|| requirement.split_once("=="))?;
+            let name = name.split('[').next()?.trim();
+            let version = version.trim();
+            if name.is_empty() || version.is_empty() || version.contains(['*', ',']) {
+                return None;
+            }
+            Some((name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+/// PEP 503 normalization so `Foo_Bar` and `foo-bar` are the same dependency.
+fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut previous_separator = false;
+    for c in name.trim().chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !previous_separator {
+                normalized.push('-');
+            }
+            previous_separator = true;
+        } else {
+            normalized.extend(c.to_lowercase());
+            previous_separator = false;
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UV_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/python_uv");
+    const POETRY_FIXTURE: &str =
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/python_poetry");
+    const REQUIREMENTS_FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/python_requirements"
+    );
+
+    fn find<'a>(dependencies: &'a [CollectorDependency], name: &str) -> &'a CollectorDependency {
+        dependencies
+            .iter()
+            .find(|d| d.name == name)
+            .unwrap_or_else(|| panic!("{} was not collected", name))
+    }
+
+    fn names(dependencies: &[CollectorDependency]) -> Vec<&str> {
+        let mut names: Vec<&str> = dependencies.iter().map(|d| d.name.as_str()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn normalizes_names_per_pep_503() {
+        assert_eq!(normalize_name("Django"), "django");
+        assert_eq!(normalize_name("typing_extensions"), "typing-extensions");
+        assert_eq!(normalize_name("Zope.Interface"), "zope-interface");
+        assert_eq!(normalize_name("foo-_.bar"), "foo-bar");
+        assert_eq!(normalize_name(" requests "), "requests");
+    }
+
+    #[test]
+    fn keeps_only_pinned_requirements() {
+        let contents =
+            read_to_string(format!("{}/requirements.txt", REQUIREMENTS_FIXTURE)).unwrap();
+        let pinned = parse_requirements(&contents);
+        let expected = [
+            ("Django", "4.2.13"),
+            ("requests", "2.32.3"),
+            ("celery", "5.4.0"),
+            ("hashed", "1.0.0"),
+        ];
+        assert_eq!(
+            pinned,
+            expected.map(|(name, version)| (name.to_string(), version.to_string()))
+        );
+    }
+
+    #[test]
+    fn requirements_file_kind_matches_whole_words() {
+        assert_eq!(
+            requirements_file_kind("requirements.txt"),
+            DependencyKind::Normal
+        );
+        assert_eq!(
+            requirements_file_kind("requirements-dev.txt"),
+            DependencyKind::Dev
+        );
+        assert_eq!(
+            requirements_file_kind("requirements_test.txt"),
+            DependencyKind::Dev
+        );
+        assert_eq!(
+            requirements_file_kind("requirements-tests.txt"),
+            DependencyKind::Dev
+        );
+        assert_eq!(
+            requirements_file_kind("requirements.development.txt"),
+            DependencyKind::Dev
+        );
+        assert_eq!(
+            requirements_file_kind("requirements-latest.txt"),
+            DependencyKind::Normal
+        );
+        assert_eq!(
+            requirements_file_kind("requirements-devops.txt"),
+            DependencyKind::Normal
+        );
+    }
+
+    #[tokio::test]
+    async fn requirements_files_are_direct_dependencies() {
+        let dependencies = collect_dependencies(REQUIREMENTS_FIXTURE).await.unwrap();
+
+        assert_eq!(
+            names(&dependencies),
+            [
+                "celery",
+                "django",
+                "hashed",
+                "pytest",
+                "requests",
+                "zope-interface"
+            ]
+        );
+        assert!(dependencies.iter().all(|d| d.is_direct));
+        assert_eq!(find(&dependencies, "pytest").kind, DependencyKind::Dev);
+        assert_eq!(
+            find(&dependencies, "zope-interface").kind,
+            DependencyKind::Normal
+        );
+    }
+
+    #[tokio::test]
+    async fn uv_lock_leaves_out_local_packages() {
+        let dependencies = collect_dependencies(UV_FIXTURE).await.unwrap();
+
+        assert_eq!(
+            names(&dependencies),
+            [
+                "anyio",
+                "httpx",
+                "idna",
+                "iniconfig",
+                "pytest",
+                "sniffio",
+                "socksio"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn uv_lock_kinds_come_from_the_project_entry() {
+        let dependencies = collect_dependencies(UV_FIXTURE).await.unwrap();
+
+        let httpx = find(&dependencies, "httpx");
+        assert!(httpx.is_direct);
+        assert_eq!(httpx.kind, DependencyKind::Normal);
+        assert_eq!(
+            httpx.depends_on.iter().find(|(name, _)| name == "anyio"),
+            Some(&("anyio".to_string(), "4.4.0".to_string()))
+        );
+
+        let pytest = find(&dependencies, "pytest");
+        assert!(pytest.is_direct);
+        assert_eq!(pytest.kind, DependencyKind::Dev);
+        assert_eq!(find(&dependencies, "iniconfig").kind, DependencyKind::Dev);
+        assert_eq!(
+            find(&dependencies, "socksio").kind,
+            DependencyKind::Optional
+        );
+
+        let sniffio = find(&dependencies, "sniffio");
+        assert!(!sniffio.is_direct);
+        assert_eq!(sniffio.kind, DependencyKind::Normal);
+    }
+
+    #[tokio::test]
+    async fn poetry_lock_kinds_come_from_groups() {
+        let dependencies = collect_dependencies(POETRY_FIXTURE).await.unwrap();
+
+        assert_eq!(
+            names(&dependencies),
+            ["certifi", "idna", "pytest", "requests", "typing-extensions"]
+        );
+        let requests = find(&dependencies, "requests");
+        assert!(requests.is_direct);
+        assert_eq!(requests.kind, DependencyKind::Normal);
+        assert_eq!(requests.depends_on.len(), 2);
+
+        let pytest = find(&dependencies, "pytest");
+        assert!(pytest.is_direct);
+        assert_eq!(pytest.kind, DependencyKind::Dev);
+        assert_eq!(
+            pytest.depends_on,
+            [("typing-extensions".to_string(), "4.12.2".to_string())]
+        );
+
+        // Needed by both groups, so it ships.
+        assert_eq!(
+            find(&dependencies, "typing-extensions").kind,
+            DependencyKind::Normal
+        );
+    }
+
+    #[test]
+    fn legacy_poetry_category_marks_dev_packages() {
+        let lockfile: PythonLockFile = toml::from_str(
+            r#"
+[[package]]
+name = "black"
+version = "24.4.2"
+category = "dev"
+optional = false
+
+[[package]]
+name = "pysocks"
+version = "1.7.1"
+category = "main"
+optional = true
+"#,
+        )
+        .unwrap();
+        assert_eq!(lockfile.package[0].locked_kind(), Some(DependencyKind::Dev));
+        assert_eq!(
+            lockfile.package[1].locked_kind(),
+            Some(DependencyKind::Optional)
+        );
+    }
+}
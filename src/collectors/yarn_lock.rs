@@ -0,0 +1,297 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::read_to_string,
+};
+
+use anyhow::{Context, Result};
+use log::debug;
+
+use crate::{
+    collectors::{
+        CollectorDependency, dedupe_dependencies, inherit_kinds,
+        javascript::{declared_dependencies, installed_package_path, resolve_kind},
+    },
+    models::{DependencyKind, ProjectLanguage},
+};
+
+#[derive(Debug, Default)]
+struct YarnLockEntry {
+    name: String,
+    /// Every `name@range` this entry resolves, e.g. `lodash@^4.17.0`.
+    descriptors: Vec<String>,
+    version: Option<String>,
+    is_workspace: bool,
+    /// `(name, range)` from the entry's `dependencies` and `optionalDependencies`.
+    dependencies: Vec<(String, String)>,
+}
+
+pub async fn collect_dependencies(path: &str) -> Result<Vec<CollectorDependency>> {
+    debug!("Scanning directory for yarn.lock: {}", path);
+
+    let yarn_lock_path = format!("{}/yarn.lock", path);
+    let contents = read_to_string(&yarn_lock_path)
+        .with_context(|| format!("Failed to read yarn.lock for {}", path))?;
+
+    let entries = parse_yarn_lock(&contents);
+
+    debug!("Found {} entries in yarn.lock", entries.len());
+
+    let mut resolved: HashMap<&str, (&str, &str)> = HashMap::new();
+    for entry in entries.iter().filter(|e| !e.is_workspace) {
+        let Some(version) = entry.version.as_deref() else {
+            continue;
+        };
+        for descriptor in &entry.descriptors {
+            resolved.insert(descriptor, (&entry.name, version));
+        }
+    }
+    // Berry prefixes plain semver ranges with the `npm:` protocol in its
+    // descriptors but not in dependency lists or package.json.
+    let resolve = |name: &str, range: &str| {
+        resolved
+            .get(format!("{}@{}", name, range).as_str())
+            .or_else(|| resolved.get(format!("{}@npm:{}", name, range).as_str()))
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+    };
+
+    // yarn.lock does not record why a package was installed, only the
+    // package.json can tell direct dev dependencies apart.
+    let mut direct_kinds: HashMap<(String, String), DependencyKind> = HashMap::new();
+    for (name, range, kind) in declared_dependencies(path) {
+        if let Some(key) = resolve(&name, &range) {
+            direct_kinds
+                .entry(key)
+                .and_modify(|existing| *existing = existing.strongest(kind))
+                .or_insert(kind);
+        }
+    }
+
+    let mut dependencies = Vec::new();
+    for entry in &entries {
+        if entry.is_workspace {
+            continue;
+        }
+        let Some(version) = entry.version.clone() else {
+            continue;
+        };
+        let name = entry.name.clone();
+        let declared = direct_kinds.get(&(name.clone(), version.clone())).copied();
+        let kind = resolve_kind(None, declared);
+        let depends_on: HashSet<(String, String)> = entry
+            .dependencies
+            .iter()
+            .filter_map(|(name, range)| resolve(name, range))
+            .collect();
+        let source_path = installed_package_path(path, &name, &version);
+        dependencies.push(CollectorDependency::new(
+            name,
+            version,
+            ProjectLanguage::Javascript,
+            source_path,
+            kind,
+            declared.is_some(),
+            depends_on.into_iter().collect(),
+        ));
+    }
+
+    let mut dependencies = dedupe_dependencies(dependencies);
+    inherit_kinds(&mut dependencies);
+
+    debug!(
+        "Found {} unique JavaScript dependencies",
+        dependencies.len()
+    );
+
+    Ok(dependencies)
+}
+
+/// Parses both the classic v1 format (`version "1.2.3"`) and the YAML based
+/// Berry format (`version: 1.2.3`). Each entry starts with an unindented line
+/// listing its descriptors, e.g. `"lodash@^4.17.0", "lodash@^4.17.21":`.
+fn parse_yarn_lock(contents: &str) -> Vec<YarnLockEntry> {
+    let mut entries = Vec::new();
+    let mut current: Option<YarnLockEntry> = None;
+    let mut in_dependencies = false;
+
+    for line in contents.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        if !line.starts_with(' ') {
+            entries.extend(current.take());
+            let header = line.trim_end_matches(':');
+            if header == "__metadata" {
+                continue;
+            }
+            let descriptors: Vec<String> = header
+                .trim_matches('"')
+                .split(", ")
+                .map(|d| d.trim_matches('"').to_string())
+                .collect();
+            let Some(first) = descriptors.first() else {
+                continue;
+            };
+            if let Some(name) = descriptor_name(first) {
+                current = Some(YarnLockEntry {
+                    name,
+                    is_workspace: first.contains("@workspace:"),
+                    descriptors,
+                    ..Default::default()
+                });
+            }
+            continue;
+        }
+
+        let Some(entry) = current.as_mut() else {
+            continue;
+        };
+        if line.starts_with("   ") {
+            if in_dependencies && let Some(dependency) = parse_dependency_line(line.trim()) {
+                entry.dependencies.push(dependency);
+            }
+            continue;
+        }
+        let field = line.trim();
+        in_dependencies = matches!(
+            field.trim_end_matches(':'),
+            "dependencies" | "optionalDependencies"
+        );
+        let version = field
+            .strip_prefix("version: ")
+            .or_else(|| field.strip_prefix("version "));
+        if let Some(version) = version {
+            let version = version.trim().trim_matches('"');
+            if !version.ends_with("-use.local") {
+                entry.version = Some(version.to_string());
+            }
+        }
+    }
+    entries.extend(current);
+
+    entries
+}
+
+/// Reads `lodash "^4.17.21"` (v1) or `lodash: ^4.17.21` (Berry), where the
+/// name may be quoted.
+fn parse_dependency_line(line: &str) -> Option<(String, String)> {
+    let (name, range) = match line.strip_prefix('"') {
+        Some(quoted) => quoted.split_once('"')?,
+        None => line.split_at(line.find([' ', ':'])?),
+    };
+    let range = range.trim_start_matches(':').trim().trim_matches('"');
+    (!name.is_empty() && !range.is_empty()).then(|| (name.to_string(), range.to_string()))
+}
+
+/// Returns `@scope/name` from `@scope/name@npm:^1.0.0` or `name` from `name@^1.0.0`.
+fn descriptor_name(descriptor: &str) -> Option<String> {
+    let index = descriptor.get(1..)?.find('@')? + 1;
+    let name = &descriptor[..index];
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/yarn_v1");
+    const BERRY_FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/yarn_berry");
+
+    fn find<'a>(dependencies: &'a [CollectorDependency], name: &str) -> &'a CollectorDependency {
+        dependencies
+            .iter()
+            .find(|d| d.name == name)
+            .unwrap_or_else(|| panic!("{} was not collected", name))
+    }
+
+    #[test]
+    fn parses_v1_entry_keys() {
+        let contents = read_to_string(format!("{}/yarn.lock", V1_FIXTURE)).unwrap();
+        let entries = parse_yarn_lock(&contents);
+
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["@scope/util", "jest-lite", "lodash"]);
+        let lodash = &entries[2];
+        assert_eq!(lodash.descriptors, ["lodash@^4.17.0", "lodash@^4.17.21"]);
+        assert_eq!(lodash.version.as_deref(), Some("4.17.21"));
+        assert_eq!(
+            entries[0].dependencies,
+            [("lodash".to_string(), "^4.17.21".to_string())]
+        );
+    }
+
+    #[test]
+    fn parses_berry_entry_keys() {
+        let contents = read_to_string(format!("{}/yarn.lock", BERRY_FIXTURE)).unwrap();
+        let entries = parse_yarn_lock(&contents);
+
+        let tokens = entries.iter().find(|e| e.name == "js-tokens").unwrap();
+        assert_eq!(tokens.descriptors, ["js-tokens@npm:^3.0.0 || ^4.0.0"]);
+        assert_eq!(tokens.version.as_deref(), Some("4.0.0"));
+
+        // `bin` entries are not dependencies.
+        let envify = entries.iter().find(|e| e.name == "loose-envify").unwrap();
+        assert_eq!(
+            envify.dependencies,
+            [("js-tokens".to_string(), "npm:^3.0.0 || ^4.0.0".to_string())]
+        );
+
+        // Workspace packages have no registry version.
+        let shared = entries.iter().find(|e| e.name == "shared").unwrap();
+        assert!(shared.is_workspace);
+        assert_eq!(shared.version, None);
+    }
+
+    #[test]
+    fn parses_descriptor_names() {
+        assert_eq!(descriptor_name("lodash@^4.17.0").as_deref(), Some("lodash"));
+        assert_eq!(
+            descriptor_name("@scope/util@npm:^1.0.0").as_deref(),
+            Some("@scope/util")
+        );
+        assert_eq!(descriptor_name("lodash"), None);
+    }
+
+    #[tokio::test]
+    async fn v1_dependencies_resolve_through_their_ranges() {
+        let dependencies = collect_dependencies(V1_FIXTURE).await.unwrap();
+
+        let util = find(&dependencies, "@scope/util");
+        assert!(util.is_direct);
+        assert_eq!(util.version, "1.2.0");
+        assert_eq!(
+            util.depends_on,
+            [("lodash".to_string(), "4.17.21".to_string())]
+        );
+
+        let jest = find(&dependencies, "jest-lite");
+        assert!(jest.is_direct);
+        assert_eq!(jest.kind, DependencyKind::Dev);
+    }
+
+    #[tokio::test]
+    async fn berry_skips_workspaces_and_resolves_npm_ranges() {
+        let dependencies = collect_dependencies(BERRY_FIXTURE).await.unwrap();
+
+        assert!(
+            dependencies
+                .iter()
+                .all(|d| d.name != "app" && d.name != "shared")
+        );
+        assert_eq!(dependencies.len(), 4);
+
+        let react = find(&dependencies, "react");
+        assert!(react.is_direct);
+        assert_eq!(react.kind, DependencyKind::Normal);
+
+        let tokens = find(&dependencies, "js-tokens");
+        assert!(!tokens.is_direct);
+        assert_eq!(tokens.kind, DependencyKind::Normal);
+        assert_eq!(
+            find(&dependencies, "loose-envify").depends_on,
+            [("js-tokens".to_string(), "4.0.0".to_string())]
+        );
+
+        assert_eq!(find(&dependencies, "typescript").kind, DependencyKind::Dev);
+    }
+}
@@ -0,0 +1,411 @@
+use std::{
+    env,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use sea_orm::Iterable;
+use serde::{Deserialize, Deserializer, de};
+
+use crate::{models::ProjectLanguage, tools::parse_language};
+
+/// Settings read from `config.toml` in the local-lore config directory, or the
+/// file given with `--config`. Environment variables override the file.
+///
+/// ```toml
+/// data_dir = "/var/lib/local-lore"
+/// schedule = "0 0 */6 * * *"
+///
+/// [jobs]
+/// rate_limit = 10
+/// rate_limit_period_secs = 5
+/// retention_days = 7
+///
+/// [jobs.retry.index_dependency_documentation]
+/// max_attempts = 5
+///
+/// [collectors]
+/// enabled = ["rust", "javascript"]
+///
+/// [[projects]]
+/// path = "/home/me/code/app"
+/// ignore = ["left-pad"]
+/// extra_paths = ["tools/codegen"]
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Where the database is kept, `LOCAL_LORE_DATA_DIR`. Defaults to
+    /// `local-lore` in the user's data directory.
+    pub data_dir: Option<PathBuf>,
+    /// When every known project is rescanned, as a cron expression with
+    /// seconds, `LOCAL_LORE_SCHEDULE`.
+    pub schedule: String,
+    pub jobs: JobsConfig,
+    pub collectors: CollectorsConfig,
+    pub projects: Vec<ProjectConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            data_dir: None,
+            schedule: "0 0 * * * *".to_string(),
+            jobs: JobsConfig::default(),
+            collectors: CollectorsConfig::default(),
+            projects: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JobsConfig {
+    /// How many application jobs may start per `rate_limit_period_secs`,
+    /// `LOCAL_LORE_JOB_RATE_LIMIT` and `LOCAL_LORE_JOB_RATE_LIMIT_PERIOD_SECS`.
+    pub rate_limit: u64,
+    pub rate_limit_period_secs: u64,
+    /// How long finished jobs are kept for their status to be looked up,
+    /// `LOCAL_LORE_JOB_RETENTION_DAYS`. Older ones are pruned on schedule.
+    pub retention_days: u64,
+    pub retry: RetryConfig,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            rate_limit: 10,
+            rate_limit_period_secs: 5,
+            retention_days: 30,
+            retry: RetryConfig::default(),
+        }
+    }
+}
+
+/// Overrides of the built-in retry policy of each job kind, the environment
+/// variables are `LOCAL_LORE_<KIND>_MAX_ATTEMPTS`,
+/// `LOCAL_LORE_<KIND>_INITIAL_BACKOFF_SECS` and
+/// `LOCAL_LORE_<KIND>_MAX_BACKOFF_SECS`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    pub gather_project_dependencies: RetryOverride,
+    pub index_dependency_documentation: RetryOverride,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryOverride {
+    pub max_attempts: Option<u32>,
+    pub initial_backoff_secs: Option<u64>,
+    pub max_backoff_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CollectorsConfig {
+    /// The languages whose collectors run, `LOCAL_LORE_COLLECTORS` as a comma
+    /// separated list. All of them by default.
+    #[serde(deserialize_with = "deserialize_languages")]
+    pub enabled: Vec<ProjectLanguage>,
+}
+
+impl Default for CollectorsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: ProjectLanguage::iter().collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// The project directory, relative to the directory of the config file.
+    pub path: PathBuf,
+    /// Names of dependencies left out of the project's index.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Directories outside the project's manifests whose dependencies count
+    /// towards the project, relative to the project.
+    #[serde(default)]
+    pub extra_paths: Vec<PathBuf>,
+}
+
+impl ProjectConfig {
+    pub fn ignores(&self, name: &str) -> bool {
+        self.ignore.iter().any(|ignored| ignored == name)
+    }
+
+    /// The extra paths that exist, resolved against the project.
+    pub fn extra_dirs(&self) -> Vec<PathBuf> {
+        self.extra_paths
+            .iter()
+            .filter_map(|extra| self.path.join(extra).canonicalize().ok())
+            .filter(|dir| dir.is_dir())
+            .collect()
+    }
+}
+
+impl Config {
+    /// Reads the config file, a missing file at the default location means
+    /// the defaults are used.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let default_path = dirs::config_dir().map(|dir| dir.join("local-lore").join("config.toml"));
+        let mut config = match (path, default_path) {
+            (Some(path), _) => Self::read(path)?,
+            (None, Some(path)) if path.is_file() => Self::read(&path)?,
+            (None, _) => Self::default(),
+        };
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        let contents = read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        Self::parse(&contents, path.parent().unwrap_or(Path::new("")))
+            .with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Parses the contents of a config file kept in `config_dir`.
+    fn parse(contents: &str, config_dir: &Path) -> Result<Self> {
+        let mut config: Self = toml::from_str(contents)?;
+        config.resolve_project_paths(config_dir)?;
+        Ok(config)
+    }
+
+    /// Projects are stored under their canonical path, the configured ones
+    /// are resolved against the config file's directory to match them.
+    fn resolve_project_paths(&mut self, config_dir: &Path) -> Result<()> {
+        for project in &mut self.projects {
+            let path = config_dir.join(&project.path);
+            project.path = path
+                .canonicalize()
+                .with_context(|| format!("Project path {} does not exist", path.display()))?;
+        }
+        Ok(())
+    }
+
+    fn apply_env(&mut self) -> Result<()> {
+        if let Some(data_dir) = env_override("LOCAL_LORE_DATA_DIR")? {
+            self.data_dir = Some(data_dir);
+        }
+        if let Some(schedule) = env_override("LOCAL_LORE_SCHEDULE")? {
+            self.schedule = schedule;
+        }
+        if let Some(rate_limit) = env_override("LOCAL_LORE_JOB_RATE_LIMIT")? {
+            self.jobs.rate_limit = rate_limit;
+        }
+        if let Some(period) = env_override("LOCAL_LORE_JOB_RATE_LIMIT_PERIOD_SECS")? {
+            self.jobs.rate_limit_period_secs = period;
+        }
+        if let Some(retention_days) = env_override("LOCAL_LORE_JOB_RETENTION_DAYS")? {
+            self.jobs.retention_days = retention_days;
+        }
+        self.jobs
+            .retry
+            .gather_project_dependencies
+            .apply_env("LOCAL_LORE_GATHER_PROJECT_DEPENDENCIES")?;
+        self.jobs
+            .retry
+            .index_dependency_documentation
+            .apply_env("LOCAL_LORE_INDEX_DEPENDENCY_DOCUMENTATION")?;
+        if let Some(enabled) = env_override::<String>("LOCAL_LORE_COLLECTORS")? {
+            self.collectors.enabled = enabled
+                .split(',')
+                .map(str::trim)
+                .filter(|language| !language.is_empty())
+                .map(parse_language)
+                .collect::<Result<_>>()?;
+        }
+        Ok(())
+    }
+
+    /// The job rate limiter panics on a zero rate, so it is rejected here.
+    fn validate(&self) -> Result<()> {
+        if self.jobs.rate_limit == 0 {
+            bail!("jobs.rate_limit (LOCAL_LORE_JOB_RATE_LIMIT) must be at least 1");
+        }
+        if self.jobs.rate_limit_period_secs == 0 {
+            bail!(
+                "jobs.rate_limit_period_secs (LOCAL_LORE_JOB_RATE_LIMIT_PERIOD_SECS) must be at least 1"
+            );
+        }
+        Ok(())
+    }
+
+    /// The overrides of the project at `path`, which is canonical.
+    pub fn project(&self, path: &str) -> Option<&ProjectConfig> {
+        self.projects.iter().find(|p| p.path == Path::new(path))
+    }
+}
+
+impl RetryOverride {
+    fn apply_env(&mut self, prefix: &str) -> Result<()> {
+        if let Some(max_attempts) = env_override(&format!("{}_MAX_ATTEMPTS", prefix))? {
+            self.max_attempts = Some(max_attempts);
+        }
+        if let Some(initial) = env_override(&format!("{}_INITIAL_BACKOFF_SECS", prefix))? {
+            self.initial_backoff_secs = Some(initial);
+        }
+        if let Some(max) = env_override(&format!("{}_MAX_BACKOFF_SECS", prefix))? {
+            self.max_backoff_secs = Some(max);
+        }
+        Ok(())
+    }
+}
+
+fn env_override<T: FromStr>(name: &str) -> Result<Option<T>> {
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| anyhow!("Invalid value '{}' for {}", value, name)),
+        Err(_) => Ok(None),
+    }
+}
+
+fn deserialize_languages<'de, D>(deserializer: D) -> Result<Vec<ProjectLanguage>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|language| parse_language(language).map_err(de::Error::custom))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+    #[test]
+    fn parses_a_config_file() {
+        let config = Config::parse(
+            r#"
+            schedule = "0 0 */6 * * *"
+
+            [jobs]
+            rate_limit = 3
+
+            [jobs.retry.index_dependency_documentation]
+            max_attempts = 5
+
+            [collectors]
+            enabled = ["rust", "go"]
+
+            [[projects]]
+            path = "cargo_multi_version"
+            ignore = ["left-pad"]
+            "#,
+            Path::new(FIXTURES),
+        )
+        .unwrap();
+
+        assert_eq!(config.schedule, "0 0 */6 * * *");
+        assert_eq!(config.jobs.rate_limit, 3);
+        // Settings the file leaves out keep their defaults.
+        assert_eq!(config.jobs.rate_limit_period_secs, 5);
+        assert_eq!(
+            config
+                .jobs
+                .retry
+                .index_dependency_documentation
+                .max_attempts,
+            Some(5)
+        );
+        assert_eq!(
+            config.collectors.enabled,
+            [ProjectLanguage::Rust, ProjectLanguage::Go]
+        );
+        // Relative project paths are resolved against the config directory.
+        let project = Path::new(FIXTURES)
+            .join("cargo_multi_version")
+            .canonicalize()
+            .unwrap();
+        assert_eq!(config.projects[0].path, project);
+        assert!(config.project(&project.to_string_lossy()).is_some());
+
+        assert!(Config::parse("unknown = 1", Path::new(FIXTURES)).is_err());
+        assert!(Config::parse("[[projects]]\npath = \"missing\"", Path::new(FIXTURES)).is_err());
+    }
+
+    #[test]
+    fn environment_overrides_the_file() {
+        let mut config = Config::parse(
+            r#"
+            [jobs]
+            rate_limit = 3
+            rate_limit_period_secs = 7
+
+            [jobs.retry.gather_project_dependencies]
+            max_attempts = 2
+            initial_backoff_secs = 4
+            "#,
+            Path::new(FIXTURES),
+        )
+        .unwrap();
+        // SAFETY: no other test reads or writes these variables.
+        unsafe {
+            env::set_var("LOCAL_LORE_JOB_RATE_LIMIT", "20");
+            env::set_var("LOCAL_LORE_GATHER_PROJECT_DEPENDENCIES_MAX_ATTEMPTS", "9");
+        }
+        let applied = config.apply_env();
+        unsafe {
+            env::remove_var("LOCAL_LORE_JOB_RATE_LIMIT");
+            env::remove_var("LOCAL_LORE_GATHER_PROJECT_DEPENDENCIES_MAX_ATTEMPTS");
+        }
+        applied.unwrap();
+
+        assert_eq!(config.jobs.rate_limit, 20);
+        assert_eq!(config.jobs.rate_limit_period_secs, 7);
+        let retry = &config.jobs.retry.gather_project_dependencies;
+        assert_eq!(retry.max_attempts, Some(9));
+        assert_eq!(retry.initial_backoff_secs, Some(4));
+    }
+
+    #[test]
+    fn rejects_a_zero_rate_limit() {
+        assert!(Config::default().validate().is_ok());
+
+        let mut config = Config::default();
+        config.jobs.rate_limit = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.jobs.rate_limit_period_secs = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn resolves_project_overrides() {
+        let root = Path::new(FIXTURES).join("cargo_multi_version");
+        let project = ProjectConfig {
+            path: root.clone(),
+            ignore: vec!["left-pad".to_string()],
+            extra_paths: vec![
+                PathBuf::from("helper"),
+                PathBuf::from("vendor/../app"),
+                PathBuf::from("missing"),
+                PathBuf::from("Cargo.toml"),
+            ],
+        };
+
+        assert!(project.ignores("left-pad"));
+        assert!(!project.ignores("left"));
+        // Missing paths and files are left out.
+        assert_eq!(
+            project.extra_dirs(),
+            [
+                root.join("helper").canonicalize().unwrap(),
+                root.join("app").canonicalize().unwrap(),
+            ]
+        );
+    }
+}
@@ -14,14 +14,23 @@ pub struct Model {
     pub last_seen_at: DateTimeUtc,
     pub first_seen_at: DateTimeUtc,
     pub last_indexed_at: Option<DateTimeUtc>,
+    pub source_path: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::dependency_document::Entity")]
+    DependencyDocument,
     #[sea_orm(has_many = "super::project_dependency::Entity")]
     ProjectDependency,
 }
 
+impl Related<super::dependency_document::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DependencyDocument.def()
+    }
+}
+
 impl Related<super::project_dependency::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProjectDependency.def()
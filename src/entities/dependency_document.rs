@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::DocumentKind;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "dependency_document")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i32,
+    pub dependency_id: i32,
+    pub kind: DocumentKind,
+    pub item_path: String,
+    pub content: String,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::dependency::Entity",
+        from = "Column::DependencyId",
+        to = "super::dependency::Column::Id"
+    )]
+    Dependency,
+}
+
+impl Related<super::dependency::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Dependency.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,32 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{JobKind, JobState};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "job")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i32,
+    /// The id the job queue hands out for the job.
+    pub task_id: String,
+    pub kind: JobKind,
+    /// The serialized `ApplicationJob`.
+    pub arguments: Json,
+    pub state: JobState,
+    pub attempts: i32,
+    pub error: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub started_at: Option<DateTimeUtc>,
+    pub finished_at: Option<DateTimeUtc>,
+    /// A retried job is not picked up again before this time.
+    pub available_at: Option<DateTimeUtc>,
+    /// The process running the job, which holds it until the lease expires.
+    pub worker_id: Option<String>,
+    pub lease_expires_at: Option<DateTimeUtc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,9 +1,19 @@
 pub mod dependency;
+pub mod dependency_document;
+pub mod dependency_edge;
+pub mod job;
 pub mod project;
 pub mod project_dependency;
+pub mod project_dependency_change;
+pub mod project_scan;
 
 pub mod prelude {
     pub use super::dependency::Entity as Dependency;
+    pub use super::dependency_document::Entity as DependencyDocument;
+    pub use super::dependency_edge::Entity as DependencyEdge;
+    pub use super::job::Entity as Job;
     pub use super::project::Entity as Project;
     pub use super::project_dependency::Entity as ProjectDependency;
+    pub use super::project_dependency_change::Entity as ProjectDependencyChange;
+    pub use super::project_scan::Entity as ProjectScan;
 }
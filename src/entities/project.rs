@@ -1,6 +1,8 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::models::ProjectLanguages;
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "project")]
 pub struct Model {
@@ -10,12 +12,24 @@ pub struct Model {
     pub path: String,
     pub last_seen_at: DateTimeUtc,
     pub first_seen_at: DateTimeUtc,
+    pub languages: ProjectLanguages,
+    pub parent_id: Option<i32>,
+    /// Set once the project's directory is no longer found on disk.
+    pub missing_since: Option<DateTimeUtc>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::project_dependency::Entity")]
     ProjectDependency,
+    #[sea_orm(has_many = "super::dependency_edge::Entity")]
+    DependencyEdge,
+    #[sea_orm(has_many = "super::project_dependency_change::Entity")]
+    ProjectDependencyChange,
+    #[sea_orm(has_many = "super::project_scan::Entity")]
+    ProjectScan,
+    #[sea_orm(belongs_to = "Entity", from = "Column::ParentId", to = "Column::Id")]
+    Parent,
 }
 
 impl Related<super::project_dependency::Entity> for Entity {
@@ -24,4 +38,22 @@ impl Related<super::project_dependency::Entity> for Entity {
     }
 }
 
+impl Related<super::dependency_edge::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::DependencyEdge.def()
+    }
+}
+
+impl Related<super::project_dependency_change::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectDependencyChange.def()
+    }
+}
+
+impl Related<super::project_scan::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectScan.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -1,6 +1,8 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::models::DependencyKind;
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "project_dependency")]
 pub struct Model {
@@ -10,6 +12,8 @@ pub struct Model {
     pub dependency_id: i32,
     pub last_seen_at: DateTimeUtc,
     pub first_seen_at: DateTimeUtc,
+    pub kind: DependencyKind,
+    pub is_direct: bool,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -0,0 +1,62 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{DependencyChangeKind, DependencyKind};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "project_dependency_change")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i32,
+    pub project_id: i32,
+    pub dependency_id: i32,
+    pub change: DependencyChangeKind,
+    pub kind: DependencyKind,
+    pub is_direct: bool,
+    pub changed_at: DateTimeUtc,
+    pub scan_id: Option<i32>,
+    /// The version an upgrade or downgrade replaced.
+    pub previous_dependency_id: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+    #[sea_orm(
+        belongs_to = "super::dependency::Entity",
+        from = "Column::DependencyId",
+        to = "super::dependency::Column::Id"
+    )]
+    Dependency,
+    #[sea_orm(
+        belongs_to = "super::project_scan::Entity",
+        from = "Column::ScanId",
+        to = "super::project_scan::Column::Id"
+    )]
+    ProjectScan,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl Related<super::dependency::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Dependency.def()
+    }
+}
+
+impl Related<super::project_scan::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectScan.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
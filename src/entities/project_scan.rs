@@ -0,0 +1,41 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "project_scan")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = true)]
+    pub id: i32,
+    pub project_id: i32,
+    pub scanned_at: DateTimeUtc,
+    pub added: i32,
+    pub removed: i32,
+    pub upgraded: i32,
+    pub downgraded: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::project::Entity",
+        from = "Column::ProjectId",
+        to = "super::project::Column::Id"
+    )]
+    Project,
+    #[sea_orm(has_many = "super::project_dependency_change::Entity")]
+    ProjectDependencyChange,
+}
+
+impl Related<super::project::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Project.def()
+    }
+}
+
+impl Related<super::project_dependency_change::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectDependencyChange.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+use crate::entities::dependency_document;
+
+/// Chunks are kept around this size so search snippets and ranking stay
+/// focused on the paragraph that matched.
+const TARGET_CHUNK_CHARS: usize = 1200;
+
+pub fn split_into_chunks(content: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in content.split("\n\n") {
+        let paragraph = paragraph.trim_matches('\n');
+        if paragraph.trim().is_empty() {
+            continue;
+        }
+        // Oversized paragraphs, such as long code blocks, are split by line.
+        let pieces: Vec<&str> = if paragraph.len() > TARGET_CHUNK_CHARS {
+            paragraph.lines().collect()
+        } else {
+            vec![paragraph]
+        };
+        for (index, piece) in pieces.into_iter().enumerate() {
+            // Only the first piece starts a paragraph, the rest are its lines.
+            let separator = if index == 0 { "\n\n" } else { "\n" };
+            if !current.is_empty()
+                && current.len() + separator.len() + piece.len() > TARGET_CHUNK_CHARS
+            {
+                chunks.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push_str(separator);
+            }
+            current.push_str(piece);
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+pub async fn delete_chunks<C: ConnectionTrait>(dependency_id: i32, db: &C) -> Result<()> {
+    db.execute(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        "DELETE FROM dependency_document_chunk WHERE dependency_id = ?",
+        [dependency_id.into()],
+    ))
+    .await?;
+    Ok(())
+}
+
+pub async fn insert_chunks<C: ConnectionTrait>(
+    document: &dependency_document::Model,
+    db: &C,
+) -> Result<()> {
+    for chunk in split_into_chunks(&document.content) {
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Sqlite,
+            "INSERT INTO dependency_document_chunk (item_path, content, dependency_id, document_id) VALUES (?, ?, ?, ?)",
+            [
+                document.item_path.clone().into(),
+                chunk.into(),
+                document.dependency_id.into(),
+                document.id.into(),
+            ],
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paragraph(c: char, len: usize) -> String {
+        c.to_string().repeat(len)
+    }
+
+    #[test]
+    fn skips_empty_content() {
+        assert!(split_into_chunks("").is_empty());
+        assert!(split_into_chunks("\n\n  \n\n\n\n").is_empty());
+    }
+
+    #[test]
+    fn fills_chunks_up_to_the_target_size() {
+        // Two paragraphs and their separator fill a chunk exactly.
+        let (a, b) = (paragraph('a', 599), paragraph('b', 599));
+        let chunks = split_into_chunks(&format!("{}\n\n{}", a, b));
+        assert_eq!(chunks, [format!("{}\n\n{}", a, b)]);
+        assert_eq!(chunks[0].len(), TARGET_CHUNK_CHARS);
+
+        // One more character starts a new chunk at the paragraph.
+        let (a, b, c) = (paragraph('a', 600), paragraph('b', 601), paragraph('c', 10));
+        let chunks = split_into_chunks(&format!("{}\n\n\n\n{}\n\n{}\n", a, b, c));
+        assert_eq!(chunks, [a, format!("{}\n\n{}", b, c)]);
+    }
+
+    #[test]
+    fn splits_an_oversized_paragraph_by_line() {
+        let lines: Vec<String> = (0..30)
+            .map(|i| format!("{:02}{}", i, "x".repeat(97)))
+            .collect();
+        let code = lines.join("\n");
+        let chunks = split_into_chunks(&format!("intro\n\n{}", code));
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= TARGET_CHUNK_CHARS));
+        // The split paragraph still starts a paragraph.
+        assert!(chunks[0].starts_with("intro\n\n00"));
+        // Nothing is lost or reordered, lines are joined as they were.
+        assert_eq!(chunks.join("\n"), format!("intro\n\n{}", code));
+
+        // A single line longer than a chunk cannot be split further.
+        let line = paragraph('x', TARGET_CHUNK_CHARS * 2);
+        assert_eq!(split_into_chunks(&line), [line]);
+    }
+}
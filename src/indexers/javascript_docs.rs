@@ -0,0 +1,148 @@
+use std::{
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow};
+use log::debug;
+use serde_json::Value;
+
+use crate::{
+    entities::dependency,
+    indexers::{ExtractedDocument, read_readme},
+    models::DocumentKind,
+};
+
+const MAX_DECLARATION_FILE_BYTES: u64 = 512 * 1024;
+const MAX_DECLARATION_FILES: usize = 500;
+const DECLARATION_EXTENSIONS: &[&str] = &[".d.ts", ".d.mts", ".d.cts"];
+
+pub fn extract_documentation(dependency: &dependency::Model) -> Result<Vec<ExtractedDocument>> {
+    // Without an install there is nothing to extract, retrying would not
+    // change that. The dependency is indexed with no documents instead and
+    // picked up again once a scan finds it installed.
+    let Some(package_dir) = dependency
+        .source_path
+        .as_ref()
+        .map(PathBuf::from)
+        .filter(|p| p.is_dir())
+    else {
+        debug!(
+            "{} {} is not installed in any scanned node_modules",
+            dependency.name, dependency.version
+        );
+        return Ok(Vec::new());
+    };
+    debug!(
+        "Extracting JavaScript documentation for {} {} from {}",
+        dependency.name,
+        dependency.version,
+        package_dir.display()
+    );
+
+    let manifest: Value = serde_json::from_str(&read_to_string(package_dir.join("package.json"))?)?;
+    if manifest.get("version").and_then(Value::as_str) != Some(dependency.version.as_str()) {
+        return Err(anyhow!(
+            "Installed {} no longer matches version {}",
+            dependency.name,
+            dependency.version
+        ));
+    }
+
+    let mut documents = Vec::new();
+
+    if let Some(readme) = read_readme(&package_dir) {
+        documents.push(readme);
+    }
+
+    if let Some(overview) = package_overview(&dependency.name, &manifest) {
+        documents.push(ExtractedDocument::new(
+            DocumentKind::Overview,
+            dependency.name.clone(),
+            overview,
+        ));
+    }
+
+    let mut declaration_files = Vec::new();
+    collect_declaration_files(&package_dir, &mut declaration_files);
+    declaration_files.sort();
+
+    for file in declaration_files.into_iter().take(MAX_DECLARATION_FILES) {
+        let Ok(relative) = file.strip_prefix(&package_dir) else {
+            continue;
+        };
+        let Ok(contents) = read_to_string(&file) else {
+            continue;
+        };
+        let item_path = format!(
+            "{}/{}",
+            dependency.name,
+            relative.to_string_lossy().replace('\\', "/")
+        );
+        documents.push(ExtractedDocument::new(
+            DocumentKind::TypeDeclaration,
+            item_path,
+            format!("```ts\n{}\n```", contents.trim_end()),
+        ));
+    }
+
+    Ok(documents)
+}
+
+fn package_overview(name: &str, manifest: &Value) -> Option<String> {
+    let description = manifest.get("description").and_then(Value::as_str);
+    let keywords: Vec<&str> = manifest
+        .get("keywords")
+        .and_then(Value::as_array)
+        .map(|k| k.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let types_entry = manifest
+        .get("types")
+        .or_else(|| manifest.get("typings"))
+        .and_then(Value::as_str);
+
+    if description.is_none() && keywords.is_empty() && types_entry.is_none() {
+        return None;
+    }
+
+    let mut overview = format!("# {}", name);
+    if let Some(description) = description {
+        overview.push_str(&format!("\n\n{}", description));
+    }
+    if !keywords.is_empty() {
+        overview.push_str(&format!("\n\nKeywords: {}", keywords.join(", ")));
+    }
+    if let Some(types_entry) = types_entry {
+        overview.push_str(&format!(
+            "\n\nType declarations entry point: {}",
+            types_entry
+        ));
+    }
+    Some(overview)
+}
+
+fn collect_declaration_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if path.is_dir() {
+            if file_name == "node_modules" || file_name.starts_with('.') {
+                continue;
+            }
+            collect_declaration_files(&path, files);
+        } else if DECLARATION_EXTENSIONS
+            .iter()
+            .any(|ext| file_name.ends_with(ext))
+            && entry
+                .metadata()
+                .is_ok_and(|m| m.len() <= MAX_DECLARATION_FILE_BYTES)
+        {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    collections::HashMap,
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use log::debug;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, EntityTrait, QueryFilter, Set, TransactionTrait,
+};
+
+use crate::{
+    LocalLoreContext,
+    entities::{
+        dependency, dependency_document,
+        prelude::{Dependency, DependencyDocument},
+    },
+    models::{DocumentKind, ProjectLanguage},
+};
+
+pub mod chunks;
+pub mod javascript_docs;
+pub mod rust_docs;
+
+/// Documentation older than this is extracted again on the next indexing run.
+const STALE_AFTER_DAYS: i64 = 7;
+const INSERT_BATCH_SIZE: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct ExtractedDocument {
+    pub kind: DocumentKind,
+    pub item_path: String,
+    pub content: String,
+}
+
+impl ExtractedDocument {
+    pub fn new(kind: DocumentKind, item_path: String, content: String) -> Self {
+        Self {
+            kind,
+            item_path,
+            content,
+        }
+    }
+}
+
+/// Reads the first `README*` file at the top of a package directory.
+pub fn read_readme(source_dir: &Path) -> Option<ExtractedDocument> {
+    let mut candidates: Vec<PathBuf> = read_dir(source_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.to_lowercase().starts_with("readme"))
+        })
+        .collect();
+    candidates.sort();
+    let readme = candidates.into_iter().next()?;
+    let content = read_to_string(&readme).ok()?;
+    let file_name = readme.file_name()?.to_str()?.to_string();
+    Some(ExtractedDocument::new(
+        DocumentKind::Readme,
+        file_name,
+        content,
+    ))
+}
+
+/// Returns `None` when no extractor exists for the dependency's language, in
+/// which case the dependency is left unindexed so a future extractor picks it up.
+async fn extract_documentation(
+    dependency: &dependency::Model,
+) -> Result<Option<Vec<ExtractedDocument>>> {
+    match dependency.language {
+        ProjectLanguage::Rust => rust_docs::extract_documentation(dependency).map(Some),
+        ProjectLanguage::Javascript => javascript_docs::extract_documentation(dependency).map(Some),
+        ProjectLanguage::Python | ProjectLanguage::Go => Ok(None),
+    }
+}
+
+pub async fn index_pending_dependencies(ctx: &Arc<LocalLoreContext>) -> Result<()> {
+    let stale_before = Utc::now() - Duration::days(STALE_AFTER_DAYS);
+    let pending = Dependency::find()
+        .filter(
+            Condition::any()
+                .add(dependency::Column::LastIndexedAt.is_null())
+                .add(dependency::Column::LastIndexedAt.lt(stale_before)),
+        )
+        .all(&ctx.db)
+        .await?;
+
+    debug!("Found {} dependencies pending indexing", pending.len());
+
+    let mut total_indexed = 0;
+    for dependency_record in pending {
+        let documents = match extract_documentation(&dependency_record).await {
+            Ok(Some(documents)) => documents,
+            Ok(None) => continue,
+            Err(e) => {
+                debug!(
+                    "Failed to extract documentation for {} {}: {}",
+                    dependency_record.name, dependency_record.version, e
+                );
+                continue;
+            }
+        };
+        // The dependency stays pending, so a later run retries it.
+        if let Err(e) = store_documents(&dependency_record, documents, ctx).await {
+            debug!(
+                "Failed to store documentation for {} {}: {}",
+                dependency_record.name, dependency_record.version, e
+            );
+            continue;
+        }
+        total_indexed += 1;
+    }
+
+    debug!("Indexed documentation for {} dependencies", total_indexed);
+
+    Ok(())
+}
+
+async fn store_documents(
+    dependency_record: &dependency::Model,
+    documents: Vec<ExtractedDocument>,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<()> {
+    let mut merged: HashMap<(DocumentKind, String), ExtractedDocument> = HashMap::new();
+    for document in documents {
+        let key = (document.kind.clone(), document.item_path.clone());
+        merged
+            .entry(key)
+            .and_modify(|existing| {
+                existing.content.push_str("\n\n");
+                existing.content.push_str(&document.content);
+            })
+            .or_insert(document);
+    }
+
+    debug!(
+        "Storing {} documents for {} {}",
+        merged.len(),
+        dependency_record.name,
+        dependency_record.version
+    );
+
+    let txn = ctx.db.begin().await?;
+
+    chunks::delete_chunks(dependency_record.id, &txn).await?;
+    DependencyDocument::delete_many()
+        .filter(dependency_document::Column::DependencyId.eq(dependency_record.id))
+        .exec(&txn)
+        .await?;
+
+    let models: Vec<dependency_document::ActiveModel> = merged
+        .into_values()
+        .map(|document| dependency_document::ActiveModel {
+            dependency_id: Set(dependency_record.id),
+            kind: Set(document.kind),
+            item_path: Set(document.item_path),
+            content: Set(document.content),
+            ..Default::default()
+        })
+        .collect();
+    for batch in models.chunks(INSERT_BATCH_SIZE) {
+        let inserted = DependencyDocument::insert_many(batch.to_vec())
+            .exec_with_returning_many(&txn)
+            .await?;
+        for document in inserted {
+            chunks::insert_chunks(&document, &txn).await?;
+        }
+    }
+
+    let mut indexed_dependency: dependency::ActiveModel = dependency_record.clone().into();
+    indexed_dependency.last_indexed_at = Set(Some(Utc::now()));
+    indexed_dependency.update(&txn).await?;
+
+    txn.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,662 @@
+use std::{
+    env,
+    fs::{read_dir, read_to_string},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Result, anyhow};
+use log::debug;
+
+use crate::{
+    entities::dependency,
+    indexers::{ExtractedDocument, read_readme},
+    models::DocumentKind,
+};
+
+const MAX_SOURCE_FILE_BYTES: u64 = 1024 * 1024;
+const MAX_ITEM_DOCUMENTS: usize = 10_000;
+const MAX_SIGNATURE_LINES: usize = 10;
+
+const ITEM_KEYWORDS: &[&str] = &[
+    "fn", "struct", "enum", "trait", "type", "const", "static", "union", "mod",
+];
+const ITEM_QUALIFIERS: &[&str] = &["async", "const", "unsafe", "default", "extern", "\"C\""];
+
+pub fn extract_documentation(dependency: &dependency::Model) -> Result<Vec<ExtractedDocument>> {
+    let source_dir = find_source_dir(dependency).ok_or_else(|| {
+        anyhow!(
+            "Sources for {} {} are not unpacked in the cargo registry",
+            dependency.name,
+            dependency.version
+        )
+    })?;
+    debug!(
+        "Extracting Rust documentation for {} {} from {}",
+        dependency.name,
+        dependency.version,
+        source_dir.display()
+    );
+
+    let crate_ident = dependency.name.replace('-', "_");
+    let mut documents = Vec::new();
+
+    if let Some(readme) = read_readme(&source_dir) {
+        documents.push(readme);
+    }
+
+    let src_dir = source_dir.join("src");
+    let mut files = Vec::new();
+    collect_source_files(&src_dir, &mut files);
+    files.sort();
+
+    let mut item_count = 0;
+    'files: for file in files {
+        let Some(module_path) = module_path_for(&src_dir, &file, &crate_ident) else {
+            continue;
+        };
+        let Ok(contents) = read_to_string(&file) else {
+            continue;
+        };
+        let parsed = parse_source_file(&contents, &module_path);
+
+        if !parsed.module_docs.is_empty() {
+            let kind = if module_path == crate_ident {
+                DocumentKind::Overview
+            } else {
+                DocumentKind::Item
+            };
+            documents.push(ExtractedDocument::new(
+                kind,
+                module_path,
+                parsed.module_docs,
+            ));
+        }
+
+        for item in parsed.items {
+            if item_count >= MAX_ITEM_DOCUMENTS {
+                debug!(
+                    "Stopping at {} items for {} {}",
+                    MAX_ITEM_DOCUMENTS, dependency.name, dependency.version
+                );
+                break 'files;
+            }
+            documents.push(item);
+            item_count += 1;
+        }
+    }
+
+    Ok(documents)
+}
+
+fn find_source_dir(dependency: &dependency::Model) -> Option<PathBuf> {
+    if let Some(source_path) = &dependency.source_path {
+        let path = PathBuf::from(source_path);
+        if path.is_dir() {
+            return Some(path);
+        }
+    }
+
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".cargo")))?;
+    let unpacked_name = format!("{}-{}", dependency.name, dependency.version);
+    read_dir(cargo_home.join("registry").join("src"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(&unpacked_name))
+        .find(|path| path.is_dir())
+}
+
+fn collect_source_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            if path.file_name().is_some_and(|n| n == "bin") {
+                continue;
+            }
+            collect_source_files(&path, files);
+        } else if path.extension().is_some_and(|e| e == "rs")
+            && entry
+                .metadata()
+                .is_ok_and(|m| m.len() <= MAX_SOURCE_FILE_BYTES)
+        {
+            files.push(path);
+        }
+    }
+}
+
+/// Maps `src/lib.rs` to `crate`, `src/a/mod.rs` and `src/a.rs` to `crate::a`.
+fn module_path_for(src_dir: &Path, file: &Path, crate_ident: &str) -> Option<String> {
+    let relative = file.strip_prefix(src_dir).ok()?.with_extension("");
+    let mut segments: Vec<String> = relative
+        .components()
+        .filter_map(|c| c.as_os_str().to_str().map(str::to_string))
+        .collect();
+    match segments.last().map(String::as_str) {
+        Some("main") if segments.len() == 1 => return None,
+        Some("lib") if segments.len() == 1 => {
+            segments.pop();
+        }
+        Some("mod") => {
+            segments.pop();
+        }
+        _ => {}
+    }
+    let mut module_path = crate_ident.to_string();
+    for segment in segments {
+        module_path.push_str("::");
+        module_path.push_str(&segment);
+    }
+    Some(module_path)
+}
+
+#[derive(Debug, Default)]
+struct ParsedSourceFile {
+    module_docs: String,
+    items: Vec<ExtractedDocument>,
+}
+
+/// Where a line starts, literals and block comments can span lines.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum LexState {
+    #[default]
+    Code,
+    String,
+    /// A raw string closed by a quote followed by this many `#`.
+    RawString(usize),
+    BlockComment,
+}
+
+#[derive(Debug)]
+struct Scope {
+    name: String,
+    depth: usize,
+    is_trait: bool,
+}
+
+/// A line based scan for `//!` and `///` doc comments. It tracks brace depth
+/// and `impl`, `trait` and inline `mod` blocks so methods get a qualified path,
+/// but it does not attempt to parse Rust.
+fn parse_source_file(contents: &str, module_path: &str) -> ParsedSourceFile {
+    let lines: Vec<&str> = contents.lines().collect();
+    let mut parsed = ParsedSourceFile::default();
+    let mut module_doc_lines: Vec<&str> = Vec::new();
+    let mut doc_lines: Vec<&str> = Vec::new();
+    let mut scopes: Vec<Scope> = Vec::new();
+    let mut pending_scope: Option<(String, bool)> = None;
+    let mut macro_export = false;
+    let mut depth = 0;
+    let mut state = LexState::Code;
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+
+        // The rest of a literal or comment is not a doc comment or an item.
+        let starts_in_code = state == LexState::Code;
+        if starts_in_code && let Some(doc) = trimmed.strip_prefix("//!") {
+            if depth == 0 {
+                module_doc_lines.push(strip_doc_prefix(doc));
+            }
+            continue;
+        }
+        if starts_in_code
+            && let Some(doc) = trimmed.strip_prefix("///")
+            && !doc.starts_with('/')
+        {
+            doc_lines.push(strip_doc_prefix(doc));
+            continue;
+        }
+        if starts_in_code && trimmed.starts_with("#[") {
+            macro_export |= trimmed.starts_with("#[macro_export");
+            continue;
+        }
+        if starts_in_code && (trimmed.starts_with("//") || trimmed.is_empty()) {
+            continue;
+        }
+
+        let item_depth = scopes.last().map_or(0, |s| s.depth);
+        let in_trait = scopes.last().is_some_and(|s| s.is_trait);
+        if starts_in_code
+            && depth == item_depth
+            && let Some((kind, name)) = parse_item_header(trimmed, !scopes.is_empty())
+        {
+            let is_public =
+                in_trait || (kind == "macro" && macro_export) || trimmed.starts_with("pub ");
+            if !doc_lines.is_empty() && is_public && kind != "impl" {
+                // Exported macros live at the crate root whatever module
+                // defines them.
+                let mut item_path = if kind == "macro" {
+                    module_path
+                        .split("::")
+                        .next()
+                        .unwrap_or(module_path)
+                        .to_string()
+                } else {
+                    let mut item_path = module_path.to_string();
+                    for scope in &scopes {
+                        item_path.push_str("::");
+                        item_path.push_str(&scope.name);
+                    }
+                    item_path
+                };
+                item_path.push_str("::");
+                item_path.push_str(&name);
+                let content = format!(
+                    "```rust\n{}\n```\n\n{}",
+                    item_signature(&lines[index..]),
+                    doc_lines.join("\n")
+                );
+                parsed.items.push(ExtractedDocument::new(
+                    DocumentKind::Item,
+                    item_path,
+                    content,
+                ));
+            }
+            if matches!(kind, "impl" | "trait" | "mod") {
+                pending_scope = Some((name, kind == "trait"));
+            }
+        }
+        doc_lines.clear();
+        macro_export = false;
+
+        for c in code_chars(line, &mut state) {
+            match c {
+                '{' => {
+                    depth += 1;
+                    if let Some((name, is_trait)) = pending_scope.take() {
+                        scopes.push(Scope {
+                            name,
+                            depth,
+                            is_trait,
+                        });
+                    }
+                }
+                '}' => {
+                    depth = depth.saturating_sub(1);
+                    while scopes.last().is_some_and(|s| s.depth > depth) {
+                        scopes.pop();
+                    }
+                }
+                ';' if depth == item_depth => {
+                    pending_scope = None;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    parsed.module_docs = module_doc_lines.join("\n").trim().to_string();
+    parsed
+}
+
+fn strip_doc_prefix(doc: &str) -> &str {
+    doc.strip_prefix(' ').unwrap_or(doc)
+}
+
+/// Returns the characters of a line outside of string, raw string and char
+/// literals and comments. `state` carries literals and block comments over to
+/// the next line.
+fn code_chars(line: &str, state: &mut LexState) -> Vec<char> {
+    let chars: Vec<char> = line.chars().collect();
+    let mut code = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match *state {
+            LexState::String => {
+                if c == '\\' {
+                    i += 1;
+                } else if c == '"' {
+                    *state = LexState::Code;
+                }
+            }
+            LexState::RawString(hashes) => {
+                if c == '"' && chars[i + 1..].iter().take_while(|&&h| h == '#').count() >= hashes {
+                    *state = LexState::Code;
+                    i += hashes;
+                }
+            }
+            LexState::BlockComment => {
+                if c == '*' && chars.get(i + 1) == Some(&'/') {
+                    *state = LexState::Code;
+                    i += 1;
+                }
+            }
+            LexState::Code => match c {
+                '/' if chars.get(i + 1) == Some(&'/') => break,
+                '/' if chars.get(i + 1) == Some(&'*') => {
+                    *state = LexState::BlockComment;
+                    i += 1;
+                }
+                '"' => *state = LexState::String,
+                'r' if starts_raw_string(&chars[..i]) => {
+                    let hashes = chars[i + 1..].iter().take_while(|&&h| h == '#').count();
+                    if chars.get(i + 1 + hashes) == Some(&'"') {
+                        *state = LexState::RawString(hashes);
+                        i += hashes + 1;
+                    } else {
+                        code.push(c);
+                    }
+                }
+                // A char literal, as opposed to a lifetime or label.
+                '\'' if chars.get(i + 1) == Some(&'\\') => {
+                    i += 3;
+                    while i < chars.len() && chars[i] != '\'' {
+                        i += 1;
+                    }
+                }
+                '\'' if chars.get(i + 2) == Some(&'\'') => i += 2,
+                _ => code.push(c),
+            },
+        }
+        i += 1;
+    }
+    code
+}
+
+/// Whether an `r` following `before` starts a raw string rather than ends an
+/// identifier, `b` being the prefix of raw byte strings.
+fn starts_raw_string(before: &[char]) -> bool {
+    let is_identifier = |c: &char| c.is_alphanumeric() || *c == '_';
+    match before {
+        [.., previous, 'b'] => !is_identifier(previous),
+        [.., previous] => !is_identifier(previous) || *previous == 'b',
+        [] => true,
+    }
+}
+
+/// Returns the item keyword and its name, e.g. `("fn", "parse")`, or
+/// `("impl", "Type")` for impl blocks.
+fn parse_item_header(line: &str, allow_private: bool) -> Option<(&'static str, String)> {
+    let mut rest = line;
+    if let Some(stripped) = rest.strip_prefix("pub ") {
+        rest = stripped;
+    } else if rest.starts_with("pub(")
+        || (!allow_private && !rest.starts_with("impl") && !rest.starts_with("macro_rules!"))
+    {
+        return None;
+    }
+
+    if let Some(stripped) = rest.strip_prefix("macro_rules!") {
+        return identifier(stripped.trim_start()).map(|name| ("macro", name));
+    }
+    if rest.starts_with("impl ") || rest.starts_with("impl<") {
+        return impl_target(rest).map(|name| ("impl", name));
+    }
+
+    loop {
+        let word = rest.split_whitespace().next()?;
+        if let Some(keyword) = ITEM_KEYWORDS.iter().find(|k| **k == word) {
+            let after = rest[word.len()..].trim_start();
+            // `const fn` and `unsafe trait` are qualifiers followed by the item.
+            if *keyword == "const"
+                && after
+                    .split_whitespace()
+                    .next()
+                    .is_some_and(|w| ITEM_KEYWORDS.contains(&w) || ITEM_QUALIFIERS.contains(&w))
+            {
+                rest = after;
+                continue;
+            }
+            let name = identifier(after)?;
+            if name == "_" {
+                return None;
+            }
+            return Some((keyword, name));
+        }
+        if ITEM_QUALIFIERS.contains(&word) {
+            rest = rest[word.len()..].trim_start();
+            continue;
+        }
+        return None;
+    }
+}
+
+fn identifier(text: &str) -> Option<String> {
+    let text = text.strip_prefix("r#").unwrap_or(text);
+    let name: String = text
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    (!name.is_empty()).then_some(name)
+}
+
+/// Extracts `Type` from `impl<T> Trait for Type<T> {` or `impl Type {`.
+fn impl_target(line: &str) -> Option<String> {
+    let mut rest = line.strip_prefix("impl")?;
+    if rest.starts_with('<') {
+        let mut open = 0;
+        let mut end = 0;
+        let mut previous = '\0';
+        for (i, c) in rest.char_indices() {
+            let is_arrow = previous == '-';
+            previous = c;
+            match c {
+                '<' => open += 1,
+                '>' if !is_arrow => {
+                    open -= 1;
+                    if open == 0 {
+                        end = i + 1;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+        rest = &rest[end..];
+    }
+    let rest = rest.trim_start();
+    let target = match rest.find(" for ") {
+        Some(index) => &rest[index + " for ".len()..],
+        None => rest,
+    };
+    let target = target.trim_start().trim_start_matches(['&', '!']);
+    // `&'a mut Type` and `dyn Trait` are named by the type or trait.
+    let target = target
+        .split_whitespace()
+        .find(|word| !word.starts_with('\'') && !matches!(*word, "mut" | "dyn"))
+        .unwrap_or(target)
+        .split('<')
+        .next()?;
+    let name = target.rsplit("::").next()?;
+    identifier(name)
+}
+
+fn item_signature(lines: &[&str]) -> String {
+    let mut signature = Vec::new();
+    for line in lines.iter().take(MAX_SIGNATURE_LINES) {
+        let trimmed = line.trim();
+        if let Some(index) = trimmed.find('{') {
+            signature.push(trimmed[..index].trim_end());
+            break;
+        }
+        signature.push(trimmed);
+        if trimmed.ends_with(';') {
+            break;
+        }
+    }
+    signature.join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(line: &str) -> String {
+        code_chars(line, &mut LexState::Code).into_iter().collect()
+    }
+
+    fn item_paths(contents: &str) -> Vec<String> {
+        parse_source_file(contents, "demo")
+            .items
+            .into_iter()
+            .map(|item| item.item_path)
+            .collect()
+    }
+
+    #[test]
+    fn code_chars_skips_strings_and_comments() {
+        assert_eq!(code(r#"let s = "{ \" }"; { // }"#), "let s = ; { ");
+        assert_eq!(code("let a = 1; /* { */ }"), "let a = 1;  }");
+    }
+
+    #[test]
+    fn code_chars_skips_char_literals() {
+        assert_eq!(code(r#"let q = '"'; {"#), "let q = ; {");
+        assert_eq!(
+            code("match c { '{' => 1, '}' => 2 }"),
+            "match c {  => 1,  => 2 }"
+        );
+        assert_eq!(code(r"let e = '\''; {"), "let e = ; {");
+        assert_eq!(code(r"let u = '\u{7b}'; {"), "let u = ; {");
+    }
+
+    #[test]
+    fn code_chars_keeps_lifetimes() {
+        assert_eq!(code("fn f<'a>(x: &'a str) {"), "fn f<'a>(x: &'a str) {");
+    }
+
+    #[test]
+    fn code_chars_skips_raw_strings() {
+        assert_eq!(code(r###"let r = r#"a "{" b"#; {"###), "let r = ; {");
+        assert_eq!(code(r###"let b = br"}"; {"###), "let b = b; {");
+        assert_eq!(code("let bar = r#type; {"), "let bar = r#type; {");
+    }
+
+    #[test]
+    fn code_chars_carries_literals_across_lines() {
+        let mut state = LexState::Code;
+        assert_eq!(
+            code_chars(r###"let s = r#"{"###, &mut state),
+            "let s = ".chars().collect::<Vec<_>>()
+        );
+        assert_eq!(state, LexState::RawString(1));
+        assert_eq!(code_chars("}\"#; {", &mut state), [';', ' ', '{']);
+        assert_eq!(state, LexState::Code);
+    }
+
+    #[test]
+    fn parse_item_header_reads_keyword_and_name() {
+        assert_eq!(
+            parse_item_header("pub fn parse(input: &str) {", false),
+            Some(("fn", "parse".to_string()))
+        );
+        assert_eq!(
+            parse_item_header("pub const unsafe fn raw() {", false),
+            Some(("fn", "raw".to_string()))
+        );
+        assert_eq!(
+            parse_item_header("pub const LIMIT: usize = 1;", false),
+            Some(("const", "LIMIT".to_string()))
+        );
+        assert_eq!(
+            parse_item_header("pub struct r#Match;", false),
+            Some(("struct", "Match".to_string()))
+        );
+        assert_eq!(
+            parse_item_header("macro_rules! ensure {", false),
+            Some(("macro", "ensure".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_item_header_skips_private_items_outside_scopes() {
+        assert_eq!(parse_item_header("fn helper() {", false), None);
+        assert_eq!(parse_item_header("pub(crate) fn helper() {", true), None);
+        assert_eq!(
+            parse_item_header("fn method(&self) {", true),
+            Some(("fn", "method".to_string()))
+        );
+        assert_eq!(parse_item_header("pub const _: () = ();", false), None);
+        assert_eq!(parse_item_header("let x = 1;", true), None);
+    }
+
+    #[test]
+    fn impl_target_names_the_implementing_type() {
+        assert_eq!(impl_target("impl Parser {"), Some("Parser".to_string()));
+        assert_eq!(
+            impl_target("impl<T: Into<String>> From<T> for Name<T> {"),
+            Some("Name".to_string())
+        );
+        assert_eq!(
+            impl_target("impl<F: Fn() -> u8> Handler for Wrapper<F> {"),
+            Some("Wrapper".to_string())
+        );
+        assert_eq!(
+            impl_target("impl fmt::Display for crate::error::Error {"),
+            Some("Error".to_string())
+        );
+        assert_eq!(
+            impl_target("impl<'a> Iterator for &'a Set {"),
+            Some("Set".to_string())
+        );
+        assert_eq!(
+            impl_target("impl<'a> Write for &'a mut Buffer {"),
+            Some("Buffer".to_string())
+        );
+    }
+
+    #[test]
+    fn module_path_for_maps_files_to_modules() {
+        let src = Path::new("/registry/demo-1.0.0/src");
+        let module = |file: &str| module_path_for(src, &src.join(file), "demo");
+        assert_eq!(module("lib.rs"), Some("demo".to_string()));
+        assert_eq!(module("parse.rs"), Some("demo::parse".to_string()));
+        assert_eq!(module("parse/mod.rs"), Some("demo::parse".to_string()));
+        assert_eq!(
+            module("parse/lexer.rs"),
+            Some("demo::parse::lexer".to_string())
+        );
+        assert_eq!(module("main.rs"), None);
+        assert_eq!(
+            module_path_for(src, Path::new("/elsewhere/lib.rs"), "demo"),
+            None
+        );
+    }
+
+    #[test]
+    fn methods_are_qualified_by_their_impl() {
+        let contents = r##"
+impl Parser {
+    /// Parses a quote.
+    pub fn quote(&self) -> char {
+        '"'
+    }
+
+    /// Parses a brace.
+    pub fn brace(&self) -> &str {
+        r#"{"#
+    }
+}
+
+/// After the impl.
+pub fn after() {}
+"##;
+        assert_eq!(
+            item_paths(contents),
+            ["demo::Parser::quote", "demo::Parser::brace", "demo::after"]
+        );
+    }
+
+    #[test]
+    fn only_exported_macros_are_documented() {
+        let contents = r#"
+/// Exported.
+#[macro_export]
+macro_rules! exported {
+    () => {};
+}
+
+/// Internal.
+macro_rules! internal {
+    () => {};
+}
+"#;
+        let parsed = parse_source_file(contents, "demo::macros");
+        let paths: Vec<&str> = parsed.items.iter().map(|i| i.item_path.as_str()).collect();
+        assert_eq!(paths, ["demo::exported"]);
+    }
+}
@@ -1,44 +1,139 @@
-use std::sync::Arc;
+use std::{any::Any, panic::AssertUnwindSafe, sync::Arc};
 
+use anyhow::anyhow;
 use apalis::prelude::*;
 use apalis_cron::CronContext;
+use chrono::{TimeDelta, Utc};
+use futures::FutureExt;
 use log::debug;
 use serde::{Deserialize, Serialize};
 
-use crate::{LocalLoreContext, collectors::gather_project_dependencies};
+use crate::{
+    LocalLoreContext,
+    collectors::{gather_project_dependencies, known_project_paths},
+    indexers::index_pending_dependencies,
+    models::JobKind,
+};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub enum ApplicationJob {
     GatherProjectDependencies(String),
+    IndexDependencyDocumentation,
+}
+
+impl ApplicationJob {
+    pub fn kind(&self) -> JobKind {
+        match self {
+            Self::GatherProjectDependencies(_) => JobKind::GatherProjectDependencies,
+            Self::IndexDependencyDocumentation => JobKind::IndexDependencyDocumentation,
+        }
+    }
 }
 
 pub async fn perform_application_job(
     job: ApplicationJob,
+    task_id: TaskId,
     ctx: Data<Arc<LocalLoreContext>>,
 ) -> Result<(), Error> {
-    debug!("Processing job: {:?}", job);
+    debug!("Processing job {}: {:?}", task_id, job);
 
+    // A panic is turned into a failure here, the worker's panic layer only
+    // sees it after the job would have been left running.
+    let job = AssertUnwindSafe(run_application_job(job, &ctx)).catch_unwind();
+    let result = ctx
+        .application_job_storage
+        .hold_lease(&task_id, job)
+        .await
+        .unwrap_or_else(|panic| Err(anyhow!("Job panicked: {}", panic_message(&*panic))));
+    // Bookkeeping failures are logged rather than failing the job itself.
+    let finished = ctx
+        .application_job_storage
+        .finish(&task_id, result.as_ref().err())
+        .await;
+    if let Err(e) = finished {
+        debug!("Failed to record end of job {}: {}", task_id, e);
+    }
+    result.map_err(|e| Error::Failed(Arc::new(e.into())))
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown cause")
+}
+
+async fn run_application_job(
+    job: ApplicationJob,
+    ctx: &Arc<LocalLoreContext>,
+) -> anyhow::Result<()> {
     match job {
         ApplicationJob::GatherProjectDependencies(path) => {
-            gather_project_dependencies(&path, &ctx)
-                .await
-                .map_err(|e| Error::Failed(Arc::new(e.into())))?;
+            let summary = gather_project_dependencies(&path, ctx).await?;
+            debug!(
+                "Gathered {} dependencies for {} across {:?} with {} workspace members",
+                summary.total_dependencies, path, summary.languages, summary.workspace_members
+            );
+            submit_application_job(ApplicationJob::IndexDependencyDocumentation, ctx).await?;
             Ok(())
         }
+        ApplicationJob::IndexDependencyDocumentation => index_pending_dependencies(ctx).await,
     }
 }
 
+/// Queues a job and returns the id it is processed under. The job is
+/// recorded first so its state can be looked up until it finishes.
+pub async fn submit_application_job(
+    job: ApplicationJob,
+    ctx: &Arc<LocalLoreContext>,
+) -> anyhow::Result<TaskId> {
+    ctx.application_job_storage.push(&job).await
+}
+
+async fn enqueue_application_job(
+    job: ApplicationJob,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<(), Error> {
+    submit_application_job(job, ctx)
+        .await
+        .map(|_| ())
+        .map_err(|e| Error::Failed(Arc::new(e.into())))
+}
+
 #[derive(Debug, Default)]
 pub struct ScheduledJob;
 
 pub async fn perform_scheduled_job(
     _job: ScheduledJob,
     cron_ctx: CronContext<chrono_tz::Tz>,
-    _ctx: Data<Arc<LocalLoreContext>>,
+    ctx: Data<Arc<LocalLoreContext>>,
 ) -> Result<(), Error> {
-    debug!(
-        "Running scheduled job at {:#?} (context available)",
-        cron_ctx.get_timestamp()
-    );
+    debug!("Running scheduled job at {:#?}", cron_ctx.get_timestamp());
+    let paths = known_project_paths(&ctx)
+        .await
+        .map_err(|e| Error::Failed(Arc::new(e.into())))?;
+    prune_finished_jobs(&ctx).await;
+    debug!("Rescanning {} known projects", paths.len());
+    for path in paths {
+        enqueue_application_job(ApplicationJob::GatherProjectDependencies(path), &ctx).await?;
+    }
+    enqueue_application_job(ApplicationJob::IndexDependencyDocumentation, &ctx).await?;
     Ok(())
 }
+
+/// Failing to prune is logged, the rescan goes ahead regardless.
+async fn prune_finished_jobs(ctx: &Arc<LocalLoreContext>) {
+    // A retention too long to subtract from now keeps every job.
+    let Some(cutoff) = i64::try_from(ctx.config.jobs.retention_days)
+        .ok()
+        .and_then(TimeDelta::try_days)
+        .and_then(|retention| Utc::now().checked_sub_signed(retention))
+    else {
+        return;
+    };
+    match ctx.application_job_storage.prune_finished(cutoff).await {
+        Ok(pruned) => debug!("Pruned {} jobs finished before {}", pruned, cutoff),
+        Err(e) => debug!("Failed to prune finished jobs: {}", e),
+    }
+}
@@ -3,13 +3,10 @@ use std::{env, fs::create_dir_all, str::FromStr, sync::Arc, time::Duration};
 use anyhow::{Result, anyhow};
 use apalis::{
     layers::WorkerBuilderExt,
-    prelude::{MemoryStorage, MessageQueue, Monitor, WorkerBuilder, WorkerFactoryFn},
+    prelude::{Monitor, WorkerBuilder, WorkerFactoryFn},
 };
 use apalis_cron::{CronStream, Schedule};
-use fastrace::{
-    collector::{Config, ConsoleReporter},
-    prelude::*,
-};
+use fastrace::{collector::ConsoleReporter, prelude::*};
 use log::debug;
 use logforth::append;
 use sea_orm::{Database, DatabaseConnection};
@@ -17,54 +14,308 @@ use sea_orm_migration::MigratorTrait;
 use tokio::try_join;
 use turbomcp::prelude::*;
 
-use crate::migrator::Migrator;
+use crate::{
+    cli::{Cli, Command, ServeArgs, Transport},
+    collectors::CollectorRegistry,
+    config::Config,
+    migrator::Migrator,
+};
 
+mod cli;
 mod collectors;
+mod config;
 mod entities;
+mod indexers;
 mod jobs;
 mod migrator;
 mod models;
+mod queue;
+mod tools;
+mod watcher;
 
 struct LocalLoreContext {
     pub db: DatabaseConnection,
+    pub application_job_storage: queue::JobStorage,
+    pub collectors: CollectorRegistry,
+    pub config: Config,
 }
 
 impl LocalLoreContext {
-    pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+    pub fn new(
+        db: DatabaseConnection,
+        application_job_storage: queue::JobStorage,
+        config: Config,
+    ) -> Self {
+        Self {
+            db,
+            application_job_storage,
+            collectors: CollectorRegistry::builtin().enabled(&config.collectors.enabled),
+            config,
+        }
+    }
+}
+
+#[cfg(test)]
+impl LocalLoreContext {
+    /// A context on a fresh in-memory database with the migrations applied.
+    pub async fn in_memory(config: Config) -> Arc<Self> {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        run_migrations(&db).await.unwrap();
+        let retry_policies = queue::RetryPolicies::new(&config.jobs.retry);
+        let application_job_storage = queue::JobStorage::new(db.clone(), retry_policies);
+        Arc::new(Self::new(db, application_job_storage, config))
     }
 }
 
 #[derive(Clone)]
 struct LocalLoreServer(Arc<LocalLoreContext>);
 
-#[server(name = "Local Lore", version = "0.1.0", transports = ["stdio"])]
+#[server(name = "Local Lore", version = "0.1.0", transports = ["stdio", "http"])]
 impl LocalLoreServer {
     fn new(ctx: Arc<LocalLoreContext>) -> Self {
         Self(ctx)
     }
+
+    #[tool(
+        "List the dependencies and versions used by indexed projects. Optionally filter by a \
+         name substring, a language (rust, javascript, python, go), an exact project path or a \
+         dependency kind (normal, dev, build, optional, peer). Use kind normal to see what a \
+         project ships in production and direct true to see only what it declares itself."
+    )]
+    async fn search_deps(
+        &self,
+        name: Option<String>,
+        language: Option<String>,
+        project_path: Option<String>,
+        kind: Option<String>,
+        direct: Option<bool>,
+    ) -> McpResult<tools::search_deps::SearchDepsResponse> {
+        let input = tools::search_deps::SearchDepsInput {
+            name,
+            language,
+            project_path,
+            kind,
+            direct,
+        };
+        tools::search_deps::search_deps(input, &self.0)
+            .await
+            .map_err(|e| McpError::Tool(e.to_string()))
+    }
+
+    #[tool(
+        "Explain why a project depends on a package by returning the chain of dependencies from \
+         one the project declares itself down to the requested package. Optionally narrow to an \
+         exact version or a language."
+    )]
+    async fn why_dependency(
+        &self,
+        project_path: String,
+        name: String,
+        version: Option<String>,
+        language: Option<String>,
+    ) -> McpResult<tools::why_dependency::WhyDependencyResponse> {
+        let input = tools::why_dependency::WhyDependencyInput {
+            project_path,
+            name,
+            version,
+            language,
+        };
+        tools::why_dependency::why_dependency(input, &self.0)
+            .await
+            .map_err(|e| McpError::Tool(e.to_string()))
+    }
+
+    #[tool(
+        "List how the dependencies of scanned projects changed over time: packages added, \
+         removed, upgraded or downgraded by each scan, newest first. Narrow it to a project \
+         path, a time range (since and until as RFC 3339 timestamps or YYYY-MM-DD dates, both \
+         inclusive), an exact package name or a language."
+    )]
+    async fn dependency_changes(
+        &self,
+        project_path: Option<String>,
+        since: Option<String>,
+        until: Option<String>,
+        name: Option<String>,
+        language: Option<String>,
+    ) -> McpResult<tools::dependency_changes::DependencyChangesResponse> {
+        let input = tools::dependency_changes::DependencyChangesInput {
+            project_path,
+            since,
+            until,
+            name,
+            language,
+        };
+        tools::dependency_changes::dependency_changes(input, &self.0)
+            .await
+            .map_err(|e| McpError::Tool(e.to_string()))
+    }
+
+    #[tool(
+        "Register a project directory and queue a scan of its dependencies. Returns the id of \
+         the queued job. Use this to cover repositories other than the one the server was \
+         started in."
+    )]
+    async fn index_project(
+        &self,
+        path: String,
+    ) -> McpResult<tools::index_project::IndexProjectResponse> {
+        let input = tools::index_project::IndexProjectInput { path };
+        tools::index_project::index_project(input, &self.0)
+            .await
+            .map_err(|e| McpError::Tool(e.to_string()))
+    }
+
+    #[tool(
+        "Queue a rescan of an already indexed project path and return the id of the queued job. \
+         Workspace members are rescanned along with their workspace."
+    )]
+    async fn scan_project(
+        &self,
+        path: String,
+    ) -> McpResult<tools::scan_project::ScanProjectResponse> {
+        let input = tools::scan_project::ScanProjectInput { path };
+        tools::scan_project::scan_project(input, &self.0)
+            .await
+            .map_err(|e| McpError::Tool(e.to_string()))
+    }
+
+    #[tool(
+        "Look up a queued job by the id index_project or scan_project returned. Reports whether \
+         it is queued, running, succeeded, failed or dead-lettered, with the error of its last \
+         failed attempt and when a retry is due."
+    )]
+    async fn job_status(&self, job_id: String) -> McpResult<tools::job_status::JobRecord> {
+        let input = tools::job_status::JobStatusInput { job_id };
+        tools::job_status::job_status(input, &self.0)
+            .await
+            .map_err(|e| McpError::Tool(e.to_string()))
+    }
+
+    #[tool(
+        "List recent background jobs, newest first. Optionally filter by state (queued, \
+         running, succeeded, failed, dead_letter) or kind (gather_project_dependencies, \
+         index_dependency_documentation)."
+    )]
+    async fn list_jobs(
+        &self,
+        state: Option<String>,
+        kind: Option<String>,
+        limit: Option<u64>,
+    ) -> McpResult<tools::list_jobs::ListJobsResponse> {
+        let input = tools::list_jobs::ListJobsInput { state, kind, limit };
+        tools::list_jobs::list_jobs(input, &self.0)
+            .await
+            .map_err(|e| McpError::Tool(e.to_string()))
+    }
+
+    #[tool(
+        "Queue a dead-lettered or failed job again under the same id, with its attempts \
+         starting over. Dead-lettered jobs are listed by list_jobs with state dead_letter."
+    )]
+    async fn replay_job(&self, job_id: String) -> McpResult<tools::job_status::JobRecord> {
+        let input = tools::replay_job::ReplayJobInput { job_id };
+        tools::replay_job::replay_job(input, &self.0)
+            .await
+            .map_err(|e| McpError::Tool(e.to_string()))
+    }
+
+    #[tool(
+        "Get the cached documentation for an exact dependency version. Without an item path the \
+         README and top-level docs are returned along with the documented item paths."
+    )]
+    async fn get_doc(
+        &self,
+        language: String,
+        name: String,
+        version: String,
+        item_path: Option<String>,
+    ) -> McpResult<tools::get_doc::GetDocResponse> {
+        let input = tools::get_doc::GetDocInput {
+            language,
+            name,
+            version,
+            item_path,
+        };
+        tools::get_doc::get_doc(input, &self.0)
+            .await
+            .map_err(|e| McpError::Tool(e.to_string()))
+    }
+
+    #[tool(
+        "Ranked keyword search over indexed dependency documentation. Narrow it to the \
+         dependencies of one project path, or to one dependency by name and version. Follow up \
+         on a result with get_doc using its item path."
+    )]
+    async fn search_docs(
+        &self,
+        query: String,
+        project_path: Option<String>,
+        language: Option<String>,
+        name: Option<String>,
+        version: Option<String>,
+        limit: Option<u64>,
+    ) -> McpResult<tools::search_docs::SearchDocsResponse> {
+        let input = tools::search_docs::SearchDocsInput {
+            query,
+            project_path,
+            language,
+            name,
+            version,
+            limit,
+        };
+        tools::search_docs::search_docs(input, &self.0)
+            .await
+            .map_err(|e| McpError::Tool(e.to_string()))
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    fastrace::set_reporter(ConsoleReporter, Config::default());
+    let cli = Cli::parse_args();
+    let command = cli.command.unwrap_or(Command::Serve(cli.serve));
+    let config = Config::load(cli.config.as_deref())?;
 
-    logforth::starter_log::builder()
-        .dispatch(|d| d.append(append::Stderr::default()))
-        .dispatch(|d| d.append(append::FastraceEvent::default()))
-        .apply();
+    // The other commands print their results, logs would drown them out.
+    if cli.verbose || matches!(command, Command::Serve(_)) {
+        fastrace::set_reporter(ConsoleReporter, fastrace::collector::Config::default());
+        logforth::starter_log::builder()
+            .dispatch(|d| d.append(append::Stderr::default()))
+            .dispatch(|d| d.append(append::FastraceEvent::default()))
+            .apply();
+    }
 
     let root = Span::root("local-lore-server", SpanContext::random());
     let _guard = root.set_local_parent();
 
-    debug!("Starting Local Lore MCP server");
-
-    let db = setup_database().await?;
+    let db = setup_database(&config).await?;
     run_migrations(&db).await?;
 
-    let project_context = Arc::new(LocalLoreContext::new(db));
+    let retry_policies = queue::RetryPolicies::new(&config.jobs.retry);
+    let application_job_storage = queue::JobStorage::new(db.clone(), retry_policies);
+    let project_context = Arc::new(LocalLoreContext::new(
+        db,
+        application_job_storage.clone(),
+        config,
+    ));
 
-    let mut application_job_storage = MemoryStorage::new();
+    let result = match command {
+        Command::Serve(args) => serve(args, project_context, application_job_storage).await,
+        Command::Scan(args) => cli::scan(args, &project_context).await,
+        Command::Deps(args) => cli::deps(args, &project_context).await,
+        Command::Search(args) => cli::search(args, &project_context).await,
+    };
+
+    fastrace::flush();
+    result
+}
+
+async fn serve(
+    args: ServeArgs,
+    project_context: Arc<LocalLoreContext>,
+    application_job_storage: queue::JobStorage,
+) -> Result<()> {
+    debug!("Starting Local Lore MCP server");
 
     let current_dir = std::env::current_dir()?;
     let current_dir_str = current_dir
@@ -75,25 +326,30 @@ async fn main() -> Result<()> {
         "Deploying initial directory scan job for: {}",
         current_dir_str
     );
-    application_job_storage
-        .enqueue(jobs::ApplicationJob::GatherProjectDependencies(
-            current_dir_str.to_string(),
-        ))
-        .await
-        .map_err(|_| anyhow!("Failed to enqueue initial scan job"))?;
+    jobs::submit_application_job(
+        jobs::ApplicationJob::GatherProjectDependencies(current_dir_str.to_string()),
+        &project_context,
+    )
+    .await
+    .map_err(|e| anyhow!("Failed to enqueue initial scan job: {}", e))?;
 
     let tz: chrono_tz::Tz = env::var("TZ")
         .map(|s| s.parse().unwrap())
         .unwrap_or_else(|_| chrono_tz::Etc::GMT);
     debug!("Timezone: {}", tz);
 
+    let config = &project_context.config;
+    let schedule = Schedule::from_str(&config.schedule)
+        .map_err(|e| anyhow!("Invalid schedule '{}': {}", config.schedule, e))?;
+    let rate_limit_period = Duration::from_secs(config.jobs.rate_limit_period_secs);
+
     let monitor = async {
         Monitor::new()
             .register(
                 WorkerBuilder::new("perform_application_job")
                     .enable_tracing()
                     .catch_panic()
-                    .rate_limit(10, Duration::new(5, 0))
+                    .rate_limit(config.jobs.rate_limit, rate_limit_period)
                     .data(project_context.clone())
                     .backend(application_job_storage)
                     .build_fn(jobs::perform_application_job),
@@ -103,10 +359,7 @@ async fn main() -> Result<()> {
                     .enable_tracing()
                     .catch_panic()
                     .data(project_context.clone())
-                    .backend(CronStream::new_with_timezone(
-                        Schedule::from_str("0 0 * * * *").unwrap(),
-                        tz,
-                    ))
+                    .backend(CronStream::new_with_timezone(schedule, tz))
                     .build_fn(jobs::perform_scheduled_job),
             )
             .run()
@@ -114,26 +367,40 @@ async fn main() -> Result<()> {
             .map_err(|e| anyhow!("{}", e))
     };
 
-    debug!("Server initialization complete, starting stdio server and job monitor");
+    debug!(
+        "Server initialization complete, starting {:?} server and job monitor",
+        args.transport
+    );
 
     let mcp_server = async {
-        LocalLoreServer::new(project_context.clone())
-            .run_stdio()
-            .await
-            .map_err(|e| anyhow!("{}", e))
+        let server = LocalLoreServer::new(project_context.clone());
+        match args.transport {
+            Transport::Stdio => server.run_stdio().await,
+            #[cfg(feature = "http")]
+            Transport::Http => {
+                server
+                    .run_http((std::net::Ipv4Addr::LOCALHOST, args.port))
+                    .await
+            }
+            #[cfg(not(feature = "http"))]
+            Transport::Http => Err("local-lore was built without the http feature".into()),
+        }
+        .map_err(|e| anyhow!("{}", e))
     };
 
-    let result = try_join!(monitor, mcp_server);
+    let project_watcher = watcher::watch_projects(project_context.clone());
 
-    fastrace::flush();
-    result.map(|_| ())
+    try_join!(monitor, mcp_server, project_watcher).map(|_| ())
 }
 
-async fn setup_database() -> Result<DatabaseConnection> {
+async fn setup_database(config: &Config) -> Result<DatabaseConnection> {
     debug!("Setting up database");
-    let data_dir = dirs::data_dir()
-        .ok_or_else(|| anyhow!("Failed to determine data directory"))?
-        .join("local-lore");
+    let data_dir = match &config.data_dir {
+        Some(data_dir) => data_dir.clone(),
+        None => dirs::data_dir()
+            .ok_or_else(|| anyhow!("Failed to determine data directory"))?
+            .join("local-lore"),
+    };
     create_dir_all(&data_dir)?;
     let db_path = data_dir.join("local-lore.db");
     let db_path_str = db_path
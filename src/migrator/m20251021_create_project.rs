@@ -13,6 +13,9 @@ pub enum Project {
     Path,
     LastSeenAt,
     FirstSeenAt,
+    Languages,
+    ParentId,
+    MissingSince,
 }
 
 #[async_trait::async_trait]
@@ -15,6 +15,7 @@ pub enum Dependency {
     LastSeenAt,
     FirstSeenAt,
     LastIndexedAt,
+    SourcePath,
 }
 
 #[async_trait::async_trait]
@@ -19,6 +19,8 @@ pub enum ProjectDependency {
     LastSeenAt,
     FirstSeenAt,
     DependencyId,
+    Kind,
+    IsDirect,
 }
 
 #[async_trait::async_trait]
@@ -0,0 +1,89 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251022_create_dependency::Dependency;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+pub static FK_DEPENDENCY_DOCUMENT_DEPENDENCY: &str = "fk_dependency_document_dependency";
+pub static UNIQUE_INDEX_DEPENDENCY_KIND_ITEM_PATH: &str =
+    "dependency_document_uq_idx_dependency_kind_item_path";
+
+#[derive(Iden)]
+pub enum DependencyDocument {
+    Table,
+    Id,
+    DependencyId,
+    Kind,
+    ItemPath,
+    Content,
+    CreatedAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DependencyDocument::Table)
+                    .col(
+                        ColumnDef::new(DependencyDocument::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(DependencyDocument::DependencyId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(DependencyDocument::Kind).text().not_null())
+                    .col(
+                        ColumnDef::new(DependencyDocument::ItemPath)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DependencyDocument::Content)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DependencyDocument::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name(FK_DEPENDENCY_DOCUMENT_DEPENDENCY)
+                            .from(DependencyDocument::Table, DependencyDocument::DependencyId)
+                            .to(Dependency::Table, Dependency::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(UNIQUE_INDEX_DEPENDENCY_KIND_ITEM_PATH)
+                    .table(DependencyDocument::Table)
+                    .col(DependencyDocument::DependencyId)
+                    .col(DependencyDocument::Kind)
+                    .col(DependencyDocument::ItemPath)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // FTS5 virtual tables cannot be expressed through the schema builder.
+        db.execute_unprepared(
+            r#"CREATE VIRTUAL TABLE dependency_document_chunk USING fts5(
+                item_path,
+                content,
+                dependency_id UNINDEXED,
+                document_id UNINDEXED,
+                tokenize = 'porter unicode61'
+            )"#,
+        )
+        .await?;
+
+        // Documents indexed before chunks existed are extracted again.
+        db.execute_unprepared("UPDATE dependency SET last_indexed_at = NULL")
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
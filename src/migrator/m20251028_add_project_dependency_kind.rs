@@ -0,0 +1,31 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251023_create_project_dependency::ProjectDependency;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectDependency::Table)
+                    .add_column(
+                        ColumnDef::new(ProjectDependency::Kind)
+                            .text()
+                            .not_null()
+                            .default("normal"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251021_create_project::Project;
+use super::m20251022_create_dependency::Dependency;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+pub static FK_DEPENDENCY_EDGE_PROJECT: &str = "fk_dependency_edge_project";
+pub static FK_DEPENDENCY_EDGE_PARENT: &str = "fk_dependency_edge_parent";
+pub static FK_DEPENDENCY_EDGE_CHILD: &str = "fk_dependency_edge_child";
+pub static UNIQUE_INDEX_DEPENDENCY_EDGE_PROJECT_PARENT_CHILD: &str =
+    "dependency_edge_uq_idx_project_parent_child";
+pub static INDEX_DEPENDENCY_EDGE_PROJECT_CHILD: &str = "dependency_edge_idx_project_child";
+
+#[derive(Iden)]
+pub enum DependencyEdge {
+    Table,
+    Id,
+    ProjectId,
+    ParentId,
+    ChildId,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(DependencyEdge::Table)
+                    .col(
+                        ColumnDef::new(DependencyEdge::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(DependencyEdge::ProjectId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(DependencyEdge::ParentId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(DependencyEdge::ChildId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name(FK_DEPENDENCY_EDGE_PROJECT)
+                            .from(DependencyEdge::Table, DependencyEdge::ProjectId)
+                            .to(Project::Table, Project::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name(FK_DEPENDENCY_EDGE_PARENT)
+                            .from(DependencyEdge::Table, DependencyEdge::ParentId)
+                            .to(Dependency::Table, Dependency::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name(FK_DEPENDENCY_EDGE_CHILD)
+                            .from(DependencyEdge::Table, DependencyEdge::ChildId)
+                            .to(Dependency::Table, Dependency::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(UNIQUE_INDEX_DEPENDENCY_EDGE_PROJECT_PARENT_CHILD)
+                    .table(DependencyEdge::Table)
+                    .col(DependencyEdge::ProjectId)
+                    .col(DependencyEdge::ParentId)
+                    .col(DependencyEdge::ChildId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_DEPENDENCY_EDGE_PROJECT_CHILD)
+                    .table(DependencyEdge::Table)
+                    .col(DependencyEdge::ProjectId)
+                    .col(DependencyEdge::ChildId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251021_create_project::Project;
+use super::m20251022_create_dependency::Dependency;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+pub static FK_PROJECT_DEPENDENCY_CHANGE_PROJECT: &str = "fk_project_dependency_change_project";
+pub static FK_PROJECT_DEPENDENCY_CHANGE_DEPENDENCY: &str =
+    "fk_project_dependency_change_dependency";
+pub static INDEX_PROJECT_DEPENDENCY_CHANGE_PROJECT_CHANGED_AT: &str =
+    "project_dependency_change_idx_project_changed_at";
+
+#[derive(Iden)]
+pub enum ProjectDependencyChange {
+    Table,
+    Id,
+    ProjectId,
+    DependencyId,
+    Change,
+    Kind,
+    IsDirect,
+    ChangedAt,
+    ScanId,
+    PreviousDependencyId,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectDependencyChange::Table)
+                    .col(
+                        ColumnDef::new(ProjectDependencyChange::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectDependencyChange::ProjectId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectDependencyChange::DependencyId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectDependencyChange::Change)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectDependencyChange::Kind)
+                            .text()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectDependencyChange::IsDirect)
+                            .boolean()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectDependencyChange::ChangedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name(FK_PROJECT_DEPENDENCY_CHANGE_PROJECT)
+                            .from(
+                                ProjectDependencyChange::Table,
+                                ProjectDependencyChange::ProjectId,
+                            )
+                            .to(Project::Table, Project::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name(FK_PROJECT_DEPENDENCY_CHANGE_DEPENDENCY)
+                            .from(
+                                ProjectDependencyChange::Table,
+                                ProjectDependencyChange::DependencyId,
+                            )
+                            .to(Dependency::Table, Dependency::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_PROJECT_DEPENDENCY_CHANGE_PROJECT_CHANGED_AT)
+                    .table(ProjectDependencyChange::Table)
+                    .col(ProjectDependencyChange::ProjectId)
+                    .col(ProjectDependencyChange::ChangedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,96 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251021_create_project::Project;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+pub static FK_PROJECT_SCAN_PROJECT: &str = "fk_project_scan_project";
+pub static INDEX_PROJECT_SCAN_PROJECT_SCANNED_AT: &str = "project_scan_idx_project_scanned_at";
+
+#[derive(Iden)]
+pub enum ProjectScan {
+    Table,
+    Id,
+    ProjectId,
+    ScannedAt,
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectScan::Table)
+                    .col(
+                        ColumnDef::new(ProjectScan::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ProjectScan::ProjectId).integer().not_null())
+                    .col(
+                        ColumnDef::new(ProjectScan::ScannedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectScan::Added)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectScan::Removed)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectScan::Upgraded)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(ProjectScan::Downgraded)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name(FK_PROJECT_SCAN_PROJECT)
+                            .from(ProjectScan::Table, ProjectScan::ProjectId)
+                            .to(Project::Table, Project::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_PROJECT_SCAN_PROJECT_SCANNED_AT)
+                    .table(ProjectScan::Table)
+                    .col(ProjectScan::ProjectId)
+                    .col(ProjectScan::ScannedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251101_create_project_dependency_change::ProjectDependencyChange;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectDependencyChange::Table)
+                    .add_column(ColumnDef::new(ProjectDependencyChange::ScanId).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectDependencyChange::Table)
+                    .add_column(
+                        ColumnDef::new(ProjectDependencyChange::PreviousDependencyId).integer(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+pub static UNIQUE_INDEX_JOB_TASK_ID: &str = "job_uq_idx_task_id";
+pub static INDEX_JOB_STATE_CREATED_AT: &str = "job_idx_state_created_at";
+
+#[derive(Iden)]
+pub enum Job {
+    Table,
+    Id,
+    TaskId,
+    Kind,
+    Arguments,
+    State,
+    Attempts,
+    Error,
+    CreatedAt,
+    StartedAt,
+    FinishedAt,
+    AvailableAt,
+    WorkerId,
+    LeaseExpiresAt,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Job::Table)
+                    .col(
+                        ColumnDef::new(Job::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Job::TaskId).text().not_null())
+                    .col(ColumnDef::new(Job::Kind).text().not_null())
+                    .col(ColumnDef::new(Job::Arguments).json().not_null())
+                    .col(ColumnDef::new(Job::State).text().not_null())
+                    .col(
+                        ColumnDef::new(Job::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(Job::Error).text())
+                    .col(
+                        ColumnDef::new(Job::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(Job::StartedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Job::FinishedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(UNIQUE_INDEX_JOB_TASK_ID)
+                    .table(Job::Table)
+                    .col(Job::TaskId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_JOB_STATE_CREATED_AT)
+                    .table(Job::Table)
+                    .col(Job::State)
+                    .col(Job::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251105_create_job::Job;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Job::Table)
+                    .add_column(ColumnDef::new(Job::AvailableAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
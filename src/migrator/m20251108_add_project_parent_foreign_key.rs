@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+use super::m20251021_create_project::Project;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+pub static FK_PROJECT_PARENT: &str = "fk_project_parent";
+pub static INDEX_PROJECT_PARENT_ID: &str = "project_idx_parent_id";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+        let previous_parent_id = Alias::new("previous_parent_id");
+
+        // SQLite cannot add a foreign key to an existing column, the column
+        // is added again with one and the links are copied over.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Project::Table)
+                    .rename_column(Project::ParentId, previous_parent_id.clone())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The schema builder only adds foreign keys when creating a table.
+        db.execute_unprepared(&format!(
+            "ALTER TABLE project ADD COLUMN parent_id INTEGER \
+             CONSTRAINT {} REFERENCES project (id) ON DELETE SET NULL",
+            FK_PROJECT_PARENT
+        ))
+        .await?;
+
+        // Links to workspaces that no longer exist are dropped.
+        db.execute_unprepared(
+            "UPDATE project SET parent_id = previous_parent_id \
+             WHERE previous_parent_id IN (SELECT id FROM project)",
+        )
+        .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Project::Table)
+                    .drop_column(previous_parent_id)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(INDEX_PROJECT_PARENT_ID)
+                    .table(Project::Table)
+                    .col(Project::ParentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Ok(())
+    }
+}
@@ -3,6 +3,22 @@ use sea_orm_migration::prelude::*;
 mod m20251021_create_project;
 mod m20251022_create_dependency;
 mod m20251023_create_project_dependency;
+mod m20251024_create_dependency_document;
+mod m20251025_add_dependency_source_path;
+mod m20251026_create_dependency_document_chunk;
+mod m20251027_add_project_languages;
+mod m20251028_add_project_dependency_kind;
+mod m20251029_add_project_dependency_is_direct;
+mod m20251030_create_dependency_edge;
+mod m20251031_add_project_parent_id;
+mod m20251101_create_project_dependency_change;
+mod m20251102_create_project_scan;
+mod m20251103_add_project_dependency_change_scan;
+mod m20251104_add_project_missing_since;
+mod m20251105_create_job;
+mod m20251106_add_job_available_at;
+mod m20251107_add_job_lease;
+mod m20251108_add_project_parent_foreign_key;
 
 pub struct Migrator;
 
@@ -13,6 +29,22 @@ impl MigratorTrait for Migrator {
             Box::new(m20251022_create_dependency::Migration),
             Box::new(m20251021_create_project::Migration),
             Box::new(m20251023_create_project_dependency::Migration),
+            Box::new(m20251024_create_dependency_document::Migration),
+            Box::new(m20251025_add_dependency_source_path::Migration),
+            Box::new(m20251026_create_dependency_document_chunk::Migration),
+            Box::new(m20251027_add_project_languages::Migration),
+            Box::new(m20251028_add_project_dependency_kind::Migration),
+            Box::new(m20251029_add_project_dependency_is_direct::Migration),
+            Box::new(m20251030_create_dependency_edge::Migration),
+            Box::new(m20251031_add_project_parent_id::Migration),
+            Box::new(m20251101_create_project_dependency_change::Migration),
+            Box::new(m20251102_create_project_scan::Migration),
+            Box::new(m20251103_add_project_dependency_change_scan::Migration),
+            Box::new(m20251104_add_project_missing_since::Migration),
+            Box::new(m20251105_create_job::Migration),
+            Box::new(m20251106_add_job_available_at::Migration),
+            Box::new(m20251107_add_job_lease::Migration),
+            Box::new(m20251108_add_project_parent_foreign_key::Migration),
         ]
     }
 }
@@ -1,8 +1,8 @@
-use sea_orm::{DeriveActiveEnum, EnumIter};
+use sea_orm::{DeriveActiveEnum, EnumIter, FromJsonQueryResult};
 use sea_orm_migration::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
 #[sea_orm(
     rs_type = "String",
     rename_all = "snake_case",
@@ -11,4 +11,95 @@ use serde::{Deserialize, Serialize};
 pub enum ProjectLanguage {
     Rust,
     Javascript,
+    Python,
+    Go,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct ProjectLanguages(pub Vec<ProjectLanguage>);
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(
+    rs_type = "String",
+    rename_all = "snake_case",
+    db_type = "String(StringLen::None)"
+)]
+pub enum DocumentKind {
+    Readme,
+    Overview,
+    Item,
+    TypeDeclaration,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(
+    rs_type = "String",
+    rename_all = "snake_case",
+    db_type = "String(StringLen::None)"
+)]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
+    Optional,
+    Peer,
+}
+
+impl DependencyKind {
+    /// When a dependency is reached through several kinds, the one closest to
+    /// what ends up shipped wins, so a crate used both at runtime and in tests
+    /// is reported as a normal dependency.
+    pub fn strongest(self, other: Self) -> Self {
+        let rank = |kind: Self| match kind {
+            Self::Normal => 4,
+            Self::Build => 3,
+            Self::Optional => 2,
+            Self::Peer => 1,
+            Self::Dev => 0,
+        };
+        if rank(other) > rank(self) {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(
+    rs_type = "String",
+    rename_all = "snake_case",
+    db_type = "String(StringLen::None)"
+)]
+pub enum DependencyChangeKind {
+    Added,
+    Removed,
+    Upgraded,
+    Downgraded,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(
+    rs_type = "String",
+    rename_all = "snake_case",
+    db_type = "String(StringLen::None)"
+)]
+pub enum JobKind {
+    GatherProjectDependencies,
+    IndexDependencyDocumentation,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize, EnumIter, DeriveActiveEnum)]
+#[sea_orm(
+    rs_type = "String",
+    rename_all = "snake_case",
+    db_type = "String(StringLen::None)"
+)]
+pub enum JobState {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    /// The job failed on every attempt its retry policy allows.
+    DeadLetter,
 }
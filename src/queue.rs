@@ -0,0 +1,680 @@
+use std::{future::Future, process, str::FromStr, sync::Arc, time::Duration};
+
+use anyhow::{Result, anyhow, bail};
+use apalis::prelude::{
+    Backend, BackendStream, Codec, Context, Controller, Error, Poller, Request, TaskId, Worker,
+};
+use apalis_core::layers::Identity;
+use chrono::{TimeDelta, Utc};
+use futures::{StreamExt, stream::BoxStream};
+use log::debug;
+use sea_orm::{
+    ActiveEnum, ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder,
+    Set, prelude::DateTimeUtc, sea_query::Expr,
+};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    select,
+    sync::Notify,
+    time::{interval, sleep, timeout},
+};
+
+use crate::{
+    config::{RetryConfig, RetryOverride},
+    entities::{job, prelude::Job},
+    jobs::ApplicationJob,
+    models::{JobKind, JobState},
+};
+
+/// Another session sharing the database queues jobs without waking this one,
+/// so the table is checked at least this often.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// A running job belongs to the process that claimed it until its lease
+/// expires. The lease is renewed while the job runs, so it only expires once
+/// that process has stopped.
+const LEASE_DURATION: Duration = Duration::from_secs(60);
+const LEASE_RENEWAL_INTERVAL: Duration = Duration::from_secs(20);
+
+/// How often a failing job is attempted and how long to wait in between.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// The wait after the given failed attempt, doubling with each attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// The policy with whatever the configuration overrides.
+    fn overridden(self, overrides: &RetryOverride) -> Self {
+        Self {
+            max_attempts: overrides
+                .max_attempts
+                .map(|max_attempts| max_attempts.max(1))
+                .unwrap_or(self.max_attempts),
+            initial_backoff: overrides
+                .initial_backoff_secs
+                .map(Duration::from_secs)
+                .unwrap_or(self.initial_backoff),
+            max_backoff: overrides
+                .max_backoff_secs
+                .map(Duration::from_secs)
+                .unwrap_or(self.max_backoff),
+        }
+    }
+}
+
+/// The retry policy of each application job kind.
+#[derive(Debug, Clone)]
+pub struct RetryPolicies {
+    pub gather_project_dependencies: RetryPolicy,
+    pub index_dependency_documentation: RetryPolicy,
+}
+
+impl Default for RetryPolicies {
+    fn default() -> Self {
+        Self {
+            // Scans mostly fail on files caught in the middle of being
+            // written, which settles within seconds.
+            gather_project_dependencies: RetryPolicy {
+                max_attempts: 5,
+                initial_backoff: Duration::from_secs(2),
+                max_backoff: Duration::from_secs(120),
+            },
+            // Indexing mostly fails on registries being unreachable.
+            index_dependency_documentation: RetryPolicy {
+                max_attempts: 3,
+                initial_backoff: Duration::from_secs(30),
+                max_backoff: Duration::from_secs(600),
+            },
+        }
+    }
+}
+
+impl RetryPolicies {
+    pub fn new(config: &RetryConfig) -> Self {
+        let default = Self::default();
+        Self {
+            gather_project_dependencies: default
+                .gather_project_dependencies
+                .overridden(&config.gather_project_dependencies),
+            index_dependency_documentation: default
+                .index_dependency_documentation
+                .overridden(&config.index_dependency_documentation),
+        }
+    }
+
+    pub fn for_kind(&self, kind: JobKind) -> &RetryPolicy {
+        match kind {
+            JobKind::GatherProjectDependencies => &self.gather_project_dependencies,
+            JobKind::IndexDependencyDocumentation => &self.index_dependency_documentation,
+        }
+    }
+}
+
+/// The application job queue, kept in the `job` table of the database so jobs
+/// queued by one session are still run when the server is started again.
+#[derive(Clone)]
+pub struct JobStorage {
+    db: DatabaseConnection,
+    controller: Controller,
+    wake: Arc<Notify>,
+    retry_policies: Arc<RetryPolicies>,
+    /// Identifies this process in the leases of the jobs it runs.
+    worker_id: Arc<str>,
+}
+
+impl JobStorage {
+    pub fn new(db: DatabaseConnection, retry_policies: RetryPolicies) -> Self {
+        Self {
+            db,
+            controller: Controller::new(),
+            wake: Arc::new(Notify::new()),
+            retry_policies: Arc::new(retry_policies),
+            worker_id: format!("{}-{}", process::id(), TaskId::new()).into(),
+        }
+    }
+
+    /// Jobs whose lease expired were interrupted by their process stopping,
+    /// they are queued again. Jobs other live processes are running keep
+    /// their renewed lease and are left alone.
+    async fn requeue_expired(&self) -> Result<u64> {
+        let result = Job::update_many()
+            .col_expr(job::Column::State, Expr::value(JobState::Queued))
+            .col_expr(job::Column::WorkerId, Expr::value(Option::<String>::None))
+            .col_expr(
+                job::Column::LeaseExpiresAt,
+                Expr::value(Option::<DateTimeUtc>::None),
+            )
+            .filter(job::Column::State.eq(JobState::Running))
+            .filter(
+                Condition::any()
+                    .add(job::Column::LeaseExpiresAt.is_null())
+                    .add(job::Column::LeaseExpiresAt.lte(Utc::now())),
+            )
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// Extends the lease of a job this process is running. Returns false when
+    /// the job is no longer held by this process.
+    async fn renew_lease(&self, task_id: &TaskId) -> Result<bool> {
+        let lease_expires_at = Utc::now() + TimeDelta::from_std(LEASE_DURATION)?;
+        let result = Job::update_many()
+            .col_expr(job::Column::LeaseExpiresAt, Expr::value(lease_expires_at))
+            .filter(job::Column::TaskId.eq(task_id.to_string()))
+            .filter(job::Column::State.eq(JobState::Running))
+            .filter(job::Column::WorkerId.eq(&*self.worker_id))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Runs the job while renewing its lease, so no other process takes it
+    /// over in the meantime.
+    pub async fn hold_lease<F: Future>(&self, task_id: &TaskId, job: F) -> F::Output {
+        let mut renewal = interval(LEASE_RENEWAL_INTERVAL);
+        // The claim itself started the lease.
+        renewal.tick().await;
+        tokio::pin!(job);
+        loop {
+            select! {
+                output = &mut job => return output,
+                _ = renewal.tick() => match self.renew_lease(task_id).await {
+                    Ok(true) => {}
+                    Ok(false) => debug!("Job {} is no longer leased to this process", task_id),
+                    Err(e) => debug!("Failed to renew the lease of job {}: {}", task_id, e),
+                },
+            }
+        }
+    }
+
+    /// Records the job as queued and returns the id it is processed under.
+    pub async fn push(&self, job: &ApplicationJob) -> Result<TaskId> {
+        let task_id = TaskId::new();
+        let record = job::ActiveModel {
+            task_id: Set(task_id.to_string()),
+            kind: Set(job.kind()),
+            arguments: Set(JsonCodec::encode(job)?),
+            state: Set(JobState::Queued),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        };
+        Job::insert(record).exec(&self.db).await?;
+        self.wake.notify_one();
+        Ok(task_id)
+    }
+
+    /// Whether the same job is already queued or running, in this process or
+    /// another one sharing the database.
+    pub async fn is_pending(&self, job: &ApplicationJob) -> Result<bool> {
+        let arguments = JsonCodec::encode(job)?;
+        let pending = Job::find()
+            .filter(job::Column::Kind.eq(job.kind()))
+            .filter(job::Column::State.is_in([JobState::Queued, JobState::Running]))
+            .all(&self.db)
+            .await?;
+        Ok(pending.iter().any(|record| record.arguments == arguments))
+    }
+
+    /// Records how a job ended. A failed job is queued again after its
+    /// backoff until it runs out of attempts, then it is dead-lettered.
+    pub async fn finish(&self, task_id: &TaskId, error: Option<&anyhow::Error>) -> Result<()> {
+        let record = Job::find()
+            .filter(job::Column::TaskId.eq(task_id.to_string()))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow!("No job with id {}", task_id))?;
+
+        let now = Utc::now();
+        // A job whose lease was taken over belongs to the process running it
+        // now, which records its end.
+        let update = Job::update_many()
+            .col_expr(job::Column::WorkerId, Expr::value(Option::<String>::None))
+            .col_expr(
+                job::Column::LeaseExpiresAt,
+                Expr::value(Option::<DateTimeUtc>::None),
+            )
+            .filter(job::Column::Id.eq(record.id))
+            .filter(job::Column::WorkerId.eq(&*self.worker_id));
+        let update = match error {
+            None => update
+                .col_expr(job::Column::State, Expr::value(JobState::Succeeded))
+                .col_expr(job::Column::Error, Expr::value(Option::<String>::None))
+                .col_expr(job::Column::FinishedAt, Expr::value(now)),
+            Some(error) => {
+                let policy = self.retry_policies.for_kind(record.kind);
+                let attempts = u32::try_from(record.attempts).unwrap_or_default();
+                let update =
+                    update.col_expr(job::Column::Error, Expr::value(format!("{:#}", error)));
+                if attempts < policy.max_attempts {
+                    let backoff = policy.backoff(attempts);
+                    debug!(
+                        "Job {} failed on attempt {} of {}, retrying in {:?}",
+                        task_id, attempts, policy.max_attempts, backoff
+                    );
+                    let available_at = now + TimeDelta::from_std(backoff)?;
+                    update
+                        .col_expr(job::Column::State, Expr::value(JobState::Queued))
+                        .col_expr(job::Column::AvailableAt, Expr::value(available_at))
+                } else {
+                    debug!("Job {} failed on every attempt, dead-lettering", task_id);
+                    update
+                        .col_expr(job::Column::State, Expr::value(JobState::DeadLetter))
+                        .col_expr(job::Column::FinishedAt, Expr::value(now))
+                }
+            }
+        };
+        if update.exec(&self.db).await?.rows_affected == 0 {
+            debug!("Job {} was taken over by another process", task_id);
+        }
+        Ok(())
+    }
+
+    /// Deletes the jobs that finished before `cutoff`. Queued and running
+    /// jobs are kept however old they are.
+    pub async fn prune_finished(&self, cutoff: DateTimeUtc) -> Result<u64> {
+        let result = Job::delete_many()
+            .filter(job::Column::State.is_in([
+                JobState::Succeeded,
+                JobState::Failed,
+                JobState::DeadLetter,
+            ]))
+            .filter(job::Column::FinishedAt.lt(cutoff))
+            .exec(&self.db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    /// Queues a dead-lettered or failed job again under the same id, with its
+    /// attempts starting over.
+    pub async fn replay(&self, task_id: &str) -> Result<job::Model> {
+        let record = Job::find()
+            .filter(job::Column::TaskId.eq(task_id))
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow!("No job with id {}", task_id))?;
+        if !matches!(record.state, JobState::DeadLetter | JobState::Failed) {
+            bail!(
+                "Job {} is {}, only dead-lettered or failed jobs can be replayed",
+                task_id,
+                record.state.to_value()
+            );
+        }
+
+        Job::update_many()
+            .col_expr(job::Column::State, Expr::value(JobState::Queued))
+            .col_expr(job::Column::Attempts, Expr::value(0))
+            .col_expr(job::Column::Error, Expr::value(Option::<String>::None))
+            .col_expr(
+                job::Column::StartedAt,
+                Expr::value(Option::<DateTimeUtc>::None),
+            )
+            .col_expr(
+                job::Column::FinishedAt,
+                Expr::value(Option::<DateTimeUtc>::None),
+            )
+            .col_expr(
+                job::Column::AvailableAt,
+                Expr::value(Option::<DateTimeUtc>::None),
+            )
+            .filter(job::Column::Id.eq(record.id))
+            .exec(&self.db)
+            .await?;
+        self.wake.notify_one();
+
+        Job::find_by_id(record.id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow!("No job with id {}", task_id))
+    }
+
+    /// Takes the oldest queued job and marks it as running under a lease held
+    /// by this process. Claiming only succeeds while the job is still queued,
+    /// so a job is never handed to two workers.
+    async fn claim_next(&self) -> Result<Option<Request<ApplicationJob, ()>>> {
+        let requeued = self.requeue_expired().await?;
+        if requeued > 0 {
+            debug!("Requeued {} jobs whose lease expired", requeued);
+        }
+
+        loop {
+            let Some(record) = Job::find()
+                .filter(job::Column::State.eq(JobState::Queued))
+                .filter(
+                    Condition::any()
+                        .add(job::Column::AvailableAt.is_null())
+                        .add(job::Column::AvailableAt.lte(Utc::now())),
+                )
+                .order_by_asc(job::Column::Id)
+                .one(&self.db)
+                .await?
+            else {
+                return Ok(None);
+            };
+
+            let now = Utc::now();
+            let claimed = Job::update_many()
+                .col_expr(job::Column::State, Expr::value(JobState::Running))
+                .col_expr(
+                    job::Column::Attempts,
+                    Expr::col(job::Column::Attempts).add(1),
+                )
+                .col_expr(job::Column::StartedAt, Expr::value(now))
+                .col_expr(job::Column::WorkerId, Expr::value(&*self.worker_id))
+                .col_expr(
+                    job::Column::LeaseExpiresAt,
+                    Expr::value(now + TimeDelta::from_std(LEASE_DURATION)?),
+                )
+                .filter(job::Column::Id.eq(record.id))
+                .filter(job::Column::State.eq(JobState::Queued))
+                .exec(&self.db)
+                .await?;
+            if claimed.rows_affected == 0 {
+                continue;
+            }
+
+            let decoded = TaskId::from_str(&record.task_id)
+                .map_err(|e| anyhow!("Invalid task id: {}", e))
+                .and_then(|task_id| {
+                    let args = JsonCodec::decode::<ApplicationJob>(record.arguments)?;
+                    Ok((task_id, args))
+                });
+            match decoded {
+                Ok((task_id, args)) => {
+                    let mut request = Request::new(args);
+                    request.parts.task_id = task_id;
+                    return Ok(Some(request));
+                }
+                Err(e) => {
+                    // The claim has to be released, or the job would be
+                    // picked up again each time its lease expires.
+                    debug!("Dropping unreadable job {}: {}", record.task_id, e);
+                    Job::update_many()
+                        .col_expr(job::Column::State, Expr::value(JobState::Failed))
+                        .col_expr(job::Column::Error, Expr::value(e.to_string()))
+                        .col_expr(job::Column::FinishedAt, Expr::value(Utc::now()))
+                        .col_expr(job::Column::WorkerId, Expr::value(Option::<String>::None))
+                        .col_expr(
+                            job::Column::LeaseExpiresAt,
+                            Expr::value(Option::<DateTimeUtc>::None),
+                        )
+                        .filter(job::Column::Id.eq(record.id))
+                        .exec(&self.db)
+                        .await?;
+                }
+            }
+        }
+    }
+}
+
+impl Backend<Request<ApplicationJob, ()>> for JobStorage {
+    type Stream =
+        BackendStream<BoxStream<'static, Result<Option<Request<ApplicationJob, ()>>, Error>>>;
+
+    type Layer = Identity;
+
+    type Codec = JsonCodec;
+
+    fn poll(self, _worker: &Worker<Context>) -> Poller<Self::Stream> {
+        let controller = self.controller.clone();
+        let stream = futures::stream::unfold(self, |storage| async move {
+            loop {
+                match storage.claim_next().await {
+                    Ok(Some(request)) => return Some((Ok(Some(request)), storage)),
+                    Ok(None) => {
+                        let _ = timeout(POLL_INTERVAL, storage.wake.notified()).await;
+                    }
+                    Err(e) => {
+                        // The stream is polled again right after an error, a
+                        // locked database would otherwise be hammered.
+                        sleep(POLL_INTERVAL).await;
+                        let error = Error::SourceError(Arc::new(e.into()));
+                        return Some((Err(error), storage));
+                    }
+                }
+            }
+        })
+        .boxed();
+        Poller::new(
+            BackendStream::new(stream, controller),
+            futures::future::pending(),
+        )
+    }
+}
+
+/// Jobs are stored as JSON in the `arguments` column.
+#[derive(Debug, Clone)]
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    type Compact = serde_json::Value;
+    type Error = serde_json::Error;
+
+    fn encode<I>(input: I) -> Result<Self::Compact, Self::Error>
+    where
+        I: Serialize,
+    {
+        serde_json::to_value(input)
+    }
+
+    fn decode<O>(input: Self::Compact) -> Result<O, Self::Error>
+    where
+        O: for<'de> Deserialize<'de>,
+    {
+        serde_json::from_value(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::Database;
+
+    use super::*;
+
+    async fn connect() -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        crate::run_migrations(&db).await.unwrap();
+        db
+    }
+
+    async fn record(storage: &JobStorage, task_id: &TaskId) -> job::Model {
+        Job::find()
+            .filter(job::Column::TaskId.eq(task_id.to_string()))
+            .one(&storage.db)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    fn scan(path: &str) -> ApplicationJob {
+        ApplicationJob::GatherProjectDependencies(path.to_string())
+    }
+
+    #[tokio::test]
+    async fn claims_each_job_once() {
+        let db = connect().await;
+        // Two processes sharing the database.
+        let first = JobStorage::new(db.clone(), RetryPolicies::default());
+        let second = JobStorage::new(db, RetryPolicies::default());
+        let a = first.push(&scan("/a")).await.unwrap();
+        let b = first.push(&scan("/b")).await.unwrap();
+
+        let claimed = first.claim_next().await.unwrap().unwrap();
+        assert_eq!(claimed.parts.task_id, a);
+        let claimed = second.claim_next().await.unwrap().unwrap();
+        assert_eq!(claimed.parts.task_id, b);
+        assert!(first.claim_next().await.unwrap().is_none());
+        assert!(second.claim_next().await.unwrap().is_none());
+
+        let a = record(&first, &a).await;
+        assert_eq!(a.state, JobState::Running);
+        assert_eq!(a.attempts, 1);
+        assert_eq!(a.worker_id.as_deref(), Some(&*first.worker_id));
+        let b = record(&first, &b).await;
+        assert_eq!(b.worker_id.as_deref(), Some(&*second.worker_id));
+    }
+
+    #[tokio::test]
+    async fn requeues_only_expired_leases() {
+        let db = connect().await;
+        let first = JobStorage::new(db.clone(), RetryPolicies::default());
+        let second = JobStorage::new(db, RetryPolicies::default());
+        let expired = first.push(&scan("/a")).await.unwrap();
+        let held = first.push(&scan("/b")).await.unwrap();
+        first.claim_next().await.unwrap().unwrap();
+        first.claim_next().await.unwrap().unwrap();
+
+        // The process running the first job stopped renewing its lease.
+        Job::update_many()
+            .col_expr(
+                job::Column::LeaseExpiresAt,
+                Expr::value(Utc::now() - TimeDelta::seconds(1)),
+            )
+            .filter(job::Column::TaskId.eq(expired.to_string()))
+            .exec(&first.db)
+            .await
+            .unwrap();
+
+        assert_eq!(second.requeue_expired().await.unwrap(), 1);
+        let requeued = record(&first, &expired).await;
+        assert_eq!(requeued.state, JobState::Queued);
+        assert_eq!(requeued.worker_id, None);
+        assert_eq!(requeued.lease_expires_at, None);
+        let running = record(&first, &held).await;
+        assert_eq!(running.state, JobState::Running);
+        assert_eq!(running.worker_id.as_deref(), Some(&*first.worker_id));
+
+        // The other process picks the interrupted job up.
+        let claimed = second.claim_next().await.unwrap().unwrap();
+        assert_eq!(claimed.parts.task_id, expired);
+        assert_eq!(record(&first, &expired).await.attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn retries_with_backoff_then_dead_letters() {
+        let policy = RetryPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(600),
+        };
+        let storage = JobStorage::new(
+            connect().await,
+            RetryPolicies {
+                gather_project_dependencies: policy,
+                ..Default::default()
+            },
+        );
+        let task_id = storage.push(&scan("/a")).await.unwrap();
+        let error = anyhow!("lockfile is being written");
+
+        storage.claim_next().await.unwrap().unwrap();
+        let before = Utc::now();
+        storage.finish(&task_id, Some(&error)).await.unwrap();
+        let after = Utc::now();
+        let retried = record(&storage, &task_id).await;
+        assert_eq!(retried.state, JobState::Queued);
+        assert_eq!(retried.attempts, 1);
+        assert_eq!(retried.error.as_deref(), Some("lockfile is being written"));
+        assert_eq!(retried.worker_id, None);
+        let backoff = TimeDelta::from_std(policy.backoff(1)).unwrap();
+        let available_at = retried.available_at.unwrap();
+        assert!(available_at >= before + backoff && available_at <= after + backoff);
+        // The job waits out its backoff.
+        assert!(storage.claim_next().await.unwrap().is_none());
+
+        Job::update_many()
+            .col_expr(job::Column::AvailableAt, Expr::value(Utc::now()))
+            .filter(job::Column::TaskId.eq(task_id.to_string()))
+            .exec(&storage.db)
+            .await
+            .unwrap();
+        storage.claim_next().await.unwrap().unwrap();
+        storage.finish(&task_id, Some(&error)).await.unwrap();
+        let dead = record(&storage, &task_id).await;
+        assert_eq!(dead.state, JobState::DeadLetter);
+        assert_eq!(dead.attempts, 2);
+        assert!(dead.finished_at.is_some());
+        assert!(storage.claim_next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn replays_only_dead_lettered_or_failed_jobs() {
+        let policy = RetryPolicy {
+            max_attempts: 1,
+            initial_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(600),
+        };
+        let storage = JobStorage::new(
+            connect().await,
+            RetryPolicies {
+                gather_project_dependencies: policy,
+                ..Default::default()
+            },
+        );
+        let dead = storage.push(&scan("/a")).await.unwrap();
+        let queued = storage.push(&scan("/b")).await.unwrap();
+        storage.claim_next().await.unwrap().unwrap();
+        storage
+            .finish(&dead, Some(&anyhow!("registry unreachable")))
+            .await
+            .unwrap();
+        assert_eq!(record(&storage, &dead).await.state, JobState::DeadLetter);
+
+        assert!(storage.replay(&queued.to_string()).await.is_err());
+        assert!(storage.replay("missing").await.is_err());
+
+        let replayed = storage.replay(&dead.to_string()).await.unwrap();
+        assert_eq!(replayed.task_id, dead.to_string());
+        assert_eq!(replayed.state, JobState::Queued);
+        assert_eq!(replayed.attempts, 0);
+        assert_eq!(replayed.error, None);
+        assert_eq!(replayed.finished_at, None);
+        assert_eq!(replayed.available_at, None);
+
+        // A job dropped as unreadable is failed and can be replayed too.
+        Job::update_many()
+            .col_expr(job::Column::State, Expr::value(JobState::Failed))
+            .filter(job::Column::TaskId.eq(queued.to_string()))
+            .exec(&storage.db)
+            .await
+            .unwrap();
+        let replayed = storage.replay(&queued.to_string()).await.unwrap();
+        assert_eq!(replayed.state, JobState::Queued);
+
+        // Replaying a queued job again is rejected, it is claimed in turn.
+        assert!(storage.replay(&dead.to_string()).await.is_err());
+        let claimed = storage.claim_next().await.unwrap().unwrap();
+        assert_eq!(claimed.parts.task_id, dead);
+    }
+
+    #[tokio::test]
+    async fn finds_pending_jobs() {
+        let db = connect().await;
+        let first = JobStorage::new(db.clone(), RetryPolicies::default());
+        let second = JobStorage::new(db, RetryPolicies::default());
+        let task_id = first.push(&scan("/a")).await.unwrap();
+
+        assert!(second.is_pending(&scan("/a")).await.unwrap());
+        assert!(!second.is_pending(&scan("/b")).await.unwrap());
+        assert!(
+            !second
+                .is_pending(&ApplicationJob::IndexDependencyDocumentation)
+                .await
+                .unwrap()
+        );
+
+        first.claim_next().await.unwrap().unwrap();
+        assert!(second.is_pending(&scan("/a")).await.unwrap());
+        first.finish(&task_id, None).await.unwrap();
+        assert!(!second.is_pending(&scan("/a")).await.unwrap());
+    }
+}
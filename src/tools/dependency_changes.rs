@@ -0,0 +1,193 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use sea_orm::{
+    ColumnTrait, EntityTrait, FromQueryResult, JoinType, QueryFilter, QueryOrder, QuerySelect,
+    RelationTrait, prelude::DateTimeUtc,
+};
+use serde::Serialize;
+
+use crate::{
+    LocalLoreContext,
+    entities::{
+        dependency,
+        prelude::{Dependency, ProjectDependencyChange, ProjectScan},
+        project, project_dependency_change, project_scan,
+    },
+    models::{DependencyChangeKind, DependencyKind, ProjectLanguage},
+    tools::{parse_date, parse_language, parse_timestamp},
+};
+
+#[derive(Debug, Default)]
+pub struct DependencyChangesInput {
+    pub project_path: Option<String>,
+    pub since: Option<String>,
+    pub until: Option<String>,
+    pub name: Option<String>,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ScanRow {
+    id: i32,
+    scanned_at: DateTimeUtc,
+    project_name: String,
+    project_path: String,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ChangeRow {
+    scan_id: Option<i32>,
+    change: DependencyChangeKind,
+    kind: DependencyKind,
+    is_direct: bool,
+    previous_dependency_id: Option<i32>,
+    name: String,
+    version: String,
+    language: ProjectLanguage,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyChange {
+    pub change: DependencyChangeKind,
+    pub name: String,
+    pub version: String,
+    /// The version an upgrade or downgrade replaced.
+    pub previous_version: Option<String>,
+    pub language: ProjectLanguage,
+    pub kind: DependencyKind,
+    pub is_direct: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanChanges {
+    pub project_name: String,
+    pub project_path: String,
+    pub scanned_at: DateTimeUtc,
+    pub changes: Vec<DependencyChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyChangesResponse {
+    pub scans: Vec<ScanChanges>,
+}
+
+pub async fn dependency_changes(
+    input: DependencyChangesInput,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<DependencyChangesResponse> {
+    let language = input.language.as_deref().map(parse_language).transpose()?;
+
+    let mut scans_query = ProjectScan::find()
+        .select_only()
+        .column(project_scan::Column::Id)
+        .column(project_scan::Column::ScannedAt)
+        .column_as(project::Column::Name, "project_name")
+        .column_as(project::Column::Path, "project_path")
+        .join(JoinType::InnerJoin, project_scan::Relation::Project.def());
+    if let Some(project_path) = input.project_path {
+        scans_query = scans_query.filter(project::Column::Path.eq(project_path));
+    }
+    if let Some(since) = input.since.as_deref().map(parse_timestamp).transpose()? {
+        scans_query = scans_query.filter(project_scan::Column::ScannedAt.gte(since));
+    }
+    if let Some(until) = input.until.as_deref() {
+        // A plain date includes the whole day, up to the next midnight.
+        scans_query = match parse_date(until).and_then(|date| date.succ_opt()) {
+            Some(next_day) => scans_query.filter(
+                project_scan::Column::ScannedAt.lt(next_day.and_time(Default::default()).and_utc()),
+            ),
+            None => {
+                scans_query.filter(project_scan::Column::ScannedAt.lte(parse_timestamp(until)?))
+            }
+        };
+    }
+    // Scans that changed nothing are not worth reporting.
+    let scans = scans_query
+        .filter(
+            project_scan::Column::Added
+                .gt(0)
+                .or(project_scan::Column::Removed.gt(0))
+                .or(project_scan::Column::Upgraded.gt(0))
+                .or(project_scan::Column::Downgraded.gt(0)),
+        )
+        .order_by_desc(project_scan::Column::ScannedAt)
+        .into_model::<ScanRow>()
+        .all(&ctx.db)
+        .await?;
+
+    let mut changes_query = ProjectDependencyChange::find()
+        .select_only()
+        .column(project_dependency_change::Column::ScanId)
+        .column(project_dependency_change::Column::Change)
+        .column(project_dependency_change::Column::Kind)
+        .column(project_dependency_change::Column::IsDirect)
+        .column(project_dependency_change::Column::PreviousDependencyId)
+        .column(dependency::Column::Name)
+        .column(dependency::Column::Version)
+        .column(dependency::Column::Language)
+        .join(
+            JoinType::InnerJoin,
+            project_dependency_change::Relation::Dependency.def(),
+        )
+        .filter(project_dependency_change::Column::ScanId.is_in(scans.iter().map(|s| s.id)));
+    if let Some(name) = input.name {
+        changes_query = changes_query.filter(dependency::Column::Name.eq(name));
+    }
+    if let Some(language) = language {
+        changes_query = changes_query.filter(dependency::Column::Language.eq(language));
+    }
+    let changes = changes_query
+        .order_by_asc(dependency::Column::Name)
+        .into_model::<ChangeRow>()
+        .all(&ctx.db)
+        .await?;
+
+    let previous_versions: HashMap<i32, String> = Dependency::find()
+        .filter(
+            dependency::Column::Id.is_in(changes.iter().filter_map(|c| c.previous_dependency_id)),
+        )
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .map(|d| (d.id, d.version))
+        .collect();
+
+    let mut changes_by_scan: BTreeMap<i32, Vec<DependencyChange>> = BTreeMap::new();
+    for change in changes {
+        let Some(scan_id) = change.scan_id else {
+            continue;
+        };
+        changes_by_scan
+            .entry(scan_id)
+            .or_default()
+            .push(DependencyChange {
+                previous_version: change
+                    .previous_dependency_id
+                    .and_then(|id| previous_versions.get(&id).cloned()),
+                change: change.change,
+                name: change.name,
+                version: change.version,
+                language: change.language,
+                kind: change.kind,
+                is_direct: change.is_direct,
+            });
+    }
+
+    let scans = scans
+        .into_iter()
+        .filter_map(|scan| {
+            Some(ScanChanges {
+                changes: changes_by_scan.remove(&scan.id)?,
+                project_name: scan.project_name,
+                project_path: scan.project_path,
+                scanned_at: scan.scanned_at,
+            })
+        })
+        .collect();
+
+    Ok(DependencyChangesResponse { scans })
+}
@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use sea_orm::{
+    ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect, prelude::DateTimeUtc,
+    sea_query::LikeExpr,
+};
+use serde::Serialize;
+
+use crate::{
+    LocalLoreContext,
+    entities::{
+        dependency, dependency_document,
+        prelude::{Dependency, DependencyDocument},
+    },
+    models::{DocumentKind, ProjectLanguage},
+    tools::parse_language,
+};
+
+const MAX_ITEM_DOCUMENTS: u64 = 50;
+const MAX_AVAILABLE_ITEMS: u64 = 500;
+
+#[derive(Debug)]
+pub struct GetDocInput {
+    pub language: String,
+    pub name: String,
+    pub version: String,
+    pub item_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocStatus {
+    Indexed,
+    NotIndexed,
+    UnknownDependency,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencySummary {
+    pub name: String,
+    pub version: String,
+    pub language: ProjectLanguage,
+    pub last_indexed_at: Option<DateTimeUtc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DocumentEntry {
+    pub kind: DocumentKind,
+    pub item_path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GetDocResponse {
+    pub status: DocStatus,
+    pub message: Option<String>,
+    pub dependency: Option<DependencySummary>,
+    pub documents: Vec<DocumentEntry>,
+    pub available_items: Vec<String>,
+}
+
+impl GetDocResponse {
+    fn empty(status: DocStatus, message: String, dependency: Option<DependencySummary>) -> Self {
+        Self {
+            status,
+            message: Some(message),
+            dependency,
+            documents: Vec::new(),
+            available_items: Vec::new(),
+        }
+    }
+}
+
+pub async fn get_doc(input: GetDocInput, ctx: &Arc<LocalLoreContext>) -> Result<GetDocResponse> {
+    let language = parse_language(&input.language)?;
+
+    let Some(dependency_record) = Dependency::find()
+        .filter(dependency::Column::Language.eq(language.clone()))
+        .filter(dependency::Column::Name.eq(&input.name))
+        .filter(dependency::Column::Version.eq(&input.version))
+        .one(&ctx.db)
+        .await?
+    else {
+        let known_versions: Vec<String> = Dependency::find()
+            .select_only()
+            .column(dependency::Column::Version)
+            .filter(dependency::Column::Language.eq(language))
+            .filter(dependency::Column::Name.eq(&input.name))
+            .order_by_asc(dependency::Column::Version)
+            .into_tuple()
+            .all(&ctx.db)
+            .await?;
+        let message = if known_versions.is_empty() {
+            format!("{} is not a dependency of any indexed project", input.name)
+        } else {
+            format!(
+                "{} {} is not a dependency of any indexed project, known versions: {}",
+                input.name,
+                input.version,
+                known_versions.join(", ")
+            )
+        };
+        return Ok(GetDocResponse::empty(
+            DocStatus::UnknownDependency,
+            message,
+            None,
+        ));
+    };
+
+    let summary = DependencySummary {
+        name: dependency_record.name.clone(),
+        version: dependency_record.version.clone(),
+        language: dependency_record.language.clone(),
+        last_indexed_at: dependency_record.last_indexed_at,
+    };
+
+    if dependency_record.last_indexed_at.is_none() {
+        return Ok(GetDocResponse::empty(
+            DocStatus::NotIndexed,
+            format!(
+                "Documentation for {} {} has not been indexed yet",
+                dependency_record.name, dependency_record.version
+            ),
+            Some(summary),
+        ));
+    }
+
+    let documents_query = DependencyDocument::find()
+        .filter(dependency_document::Column::DependencyId.eq(dependency_record.id));
+
+    let (documents, available_items) = match input.item_path {
+        Some(item_path) => {
+            let mut documents = documents_query
+                .clone()
+                .filter(dependency_document::Column::ItemPath.eq(&item_path))
+                .all(&ctx.db)
+                .await?;
+            if documents.is_empty() {
+                documents = documents_query
+                    .filter(dependency_document::Column::ItemPath.like(prefix_pattern(&item_path)))
+                    .order_by_asc(dependency_document::Column::ItemPath)
+                    .limit(MAX_ITEM_DOCUMENTS)
+                    .all(&ctx.db)
+                    .await?;
+            }
+            (documents, Vec::new())
+        }
+        None => {
+            let documents = documents_query
+                .clone()
+                .filter(
+                    dependency_document::Column::Kind
+                        .is_in([DocumentKind::Readme, DocumentKind::Overview]),
+                )
+                .order_by_asc(dependency_document::Column::Kind)
+                .all(&ctx.db)
+                .await?;
+            let available_items: Vec<String> = documents_query
+                .select_only()
+                .column(dependency_document::Column::ItemPath)
+                .filter(
+                    dependency_document::Column::Kind
+                        .is_in([DocumentKind::Item, DocumentKind::TypeDeclaration]),
+                )
+                .order_by_asc(dependency_document::Column::ItemPath)
+                .limit(MAX_AVAILABLE_ITEMS)
+                .into_tuple()
+                .all(&ctx.db)
+                .await?;
+            (documents, available_items)
+        }
+    };
+
+    let message = documents
+        .is_empty()
+        .then(|| "No documentation matched the requested item".to_string());
+
+    Ok(GetDocResponse {
+        status: DocStatus::Indexed,
+        message,
+        dependency: Some(summary),
+        documents: documents
+            .into_iter()
+            .map(|d| DocumentEntry {
+                kind: d.kind,
+                item_path: d.item_path,
+                content: d.content,
+            })
+            .collect(),
+        available_items,
+    })
+}
+
+/// Matches the item paths starting with `prefix`. Its `%` and `_` are taken
+/// literally rather than as wildcards, Rust paths are full of underscores.
+fn prefix_pattern(prefix: &str) -> LikeExpr {
+    let escaped = prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    LikeExpr::new(format!("{}%", escaped)).escape('\\')
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use sea_orm::{ActiveValue::Set, EntityTrait};
+
+    use super::*;
+    use crate::config::Config;
+
+    #[tokio::test]
+    async fn matches_item_path_prefixes_literally() {
+        let ctx = LocalLoreContext::in_memory(Config::default()).await;
+        let now = Utc::now();
+        let dependency = Dependency::insert(dependency::ActiveModel {
+            name: Set("serde_json".to_string()),
+            version: Set("1.0.0".to_string()),
+            language: Set(ProjectLanguage::Rust),
+            first_seen_at: Set(now),
+            last_seen_at: Set(now),
+            last_indexed_at: Set(Some(now)),
+            ..Default::default()
+        })
+        .exec(&ctx.db)
+        .await
+        .unwrap();
+        for item_path in ["serde_json::to_value", "serdexjson::to_value", "100%::x"] {
+            DependencyDocument::insert(dependency_document::ActiveModel {
+                dependency_id: Set(dependency.last_insert_id),
+                kind: Set(DocumentKind::Item),
+                item_path: Set(item_path.to_string()),
+                content: Set(String::new()),
+                created_at: Set(now),
+                ..Default::default()
+            })
+            .exec(&ctx.db)
+            .await
+            .unwrap();
+        }
+
+        let item_paths = |item_path: &str| {
+            let input = GetDocInput {
+                language: "rust".to_string(),
+                name: "serde_json".to_string(),
+                version: "1.0.0".to_string(),
+                item_path: Some(item_path.to_string()),
+            };
+            let ctx = ctx.clone();
+            async move {
+                get_doc(input, &ctx)
+                    .await
+                    .unwrap()
+                    .documents
+                    .into_iter()
+                    .map(|d| d.item_path)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(item_paths("serde_json").await, ["serde_json::to_value"]);
+        assert_eq!(item_paths("100%").await, ["100%::x"]);
+        assert!(item_paths("1%").await.is_empty());
+        assert_eq!(item_paths("serde_").await, ["serde_json::to_value"]);
+    }
+}
@@ -0,0 +1,72 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::{Result, anyhow};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::Serialize;
+
+use crate::{
+    LocalLoreContext,
+    entities::{prelude::Project, project},
+    jobs::{ApplicationJob, submit_application_job},
+    models::ProjectLanguage,
+};
+
+#[derive(Debug)]
+pub struct IndexProjectInput {
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexProjectResponse {
+    pub job_id: String,
+    pub path: String,
+    pub languages: Vec<ProjectLanguage>,
+    /// Whether the path was already a known project.
+    pub already_known: bool,
+}
+
+pub async fn index_project(
+    input: IndexProjectInput,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<IndexProjectResponse> {
+    let path = Path::new(&input.path)
+        .canonicalize()
+        .map_err(|e| anyhow!("Cannot access {}: {}", input.path, e))?;
+    if !path.is_dir() {
+        return Err(anyhow!("{} is not a directory", input.path));
+    }
+    let path = path
+        .to_str()
+        .ok_or_else(|| anyhow!("Project path contains invalid unicode"))?
+        .to_string();
+
+    let languages: Vec<ProjectLanguage> = ctx
+        .collectors
+        .detect(Path::new(&path))
+        .iter()
+        .map(|c| c.language())
+        .collect();
+    if languages.is_empty() {
+        return Err(anyhow!(
+            "No supported manifest or lockfile found in {}",
+            path
+        ));
+    }
+
+    let already_known = Project::find()
+        .filter(project::Column::Path.eq(&path))
+        .one(&ctx.db)
+        .await?
+        .is_some();
+
+    let job_id =
+        submit_application_job(ApplicationJob::GatherProjectDependencies(path.clone()), ctx)
+            .await?;
+
+    Ok(IndexProjectResponse {
+        job_id: job_id.to_string(),
+        path,
+        languages,
+        already_known,
+    })
+}
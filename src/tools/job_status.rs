@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, prelude::DateTimeUtc};
+use serde::Serialize;
+
+use crate::{
+    LocalLoreContext,
+    entities::{job, prelude::Job},
+    models::{JobKind, JobState},
+};
+
+#[derive(Debug)]
+pub struct JobStatusInput {
+    pub job_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobRecord {
+    pub job_id: String,
+    pub kind: JobKind,
+    pub arguments: serde_json::Value,
+    pub state: JobState,
+    pub attempts: i32,
+    pub error: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub started_at: Option<DateTimeUtc>,
+    pub finished_at: Option<DateTimeUtc>,
+    pub available_at: Option<DateTimeUtc>,
+}
+
+impl From<job::Model> for JobRecord {
+    fn from(model: job::Model) -> Self {
+        Self {
+            job_id: model.task_id,
+            kind: model.kind,
+            arguments: model.arguments,
+            state: model.state,
+            attempts: model.attempts,
+            error: model.error,
+            created_at: model.created_at,
+            started_at: model.started_at,
+            finished_at: model.finished_at,
+            available_at: model.available_at,
+        }
+    }
+}
+
+pub async fn job_status(input: JobStatusInput, ctx: &Arc<LocalLoreContext>) -> Result<JobRecord> {
+    Job::find()
+        .filter(job::Column::TaskId.eq(&input.job_id))
+        .one(&ctx.db)
+        .await?
+        .map(JobRecord::from)
+        .ok_or_else(|| anyhow!("No job with id {}", input.job_id))
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use serde::Serialize;
+
+use crate::{
+    LocalLoreContext,
+    entities::{job, prelude::Job},
+    tools::{job_status::JobRecord, parse_job_kind, parse_job_state},
+};
+
+const DEFAULT_LIMIT: u64 = 20;
+const MAX_LIMIT: u64 = 200;
+
+#[derive(Debug, Default)]
+pub struct ListJobsInput {
+    pub state: Option<String>,
+    pub kind: Option<String>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListJobsResponse {
+    pub jobs: Vec<JobRecord>,
+}
+
+pub async fn list_jobs(
+    input: ListJobsInput,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<ListJobsResponse> {
+    let mut query = Job::find();
+    if let Some(state) = input.state {
+        query = query.filter(job::Column::State.eq(parse_job_state(&state)?));
+    }
+    if let Some(kind) = input.kind {
+        query = query.filter(job::Column::Kind.eq(parse_job_kind(&kind)?));
+    }
+
+    let jobs = query
+        .order_by_desc(job::Column::CreatedAt)
+        .order_by_desc(job::Column::Id)
+        .limit(input.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT))
+        .all(&ctx.db)
+        .await?
+        .into_iter()
+        .map(JobRecord::from)
+        .collect();
+
+    Ok(ListJobsResponse { jobs })
+}
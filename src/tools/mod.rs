@@ -0,0 +1,68 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, NaiveDate, Utc};
+use sea_orm::{ActiveEnum, Iterable};
+
+use crate::models::{DependencyKind, JobKind, JobState, ProjectLanguage};
+
+pub mod dependency_changes;
+pub mod get_doc;
+pub mod index_project;
+pub mod job_status;
+pub mod list_jobs;
+pub mod replay_job;
+pub mod scan_project;
+pub mod search_deps;
+pub mod search_docs;
+pub mod why_dependency;
+
+pub fn parse_language(language: &str) -> Result<ProjectLanguage> {
+    parse_active_enum("language", language)
+}
+
+pub fn parse_dependency_kind(kind: &str) -> Result<DependencyKind> {
+    parse_active_enum("dependency kind", kind)
+}
+
+pub fn parse_job_kind(kind: &str) -> Result<JobKind> {
+    parse_active_enum("job kind", kind)
+}
+
+pub fn parse_job_state(state: &str) -> Result<JobState> {
+    parse_active_enum("job state", state)
+}
+
+/// Accepts an RFC 3339 timestamp or a plain `YYYY-MM-DD` date, which is
+/// taken as midnight UTC.
+pub fn parse_timestamp(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    parse_date(value)
+        .map(|date| date.and_time(Default::default()).and_utc())
+        .ok_or_else(|| {
+            anyhow!(
+                "Unsupported timestamp '{}', expected RFC 3339 or YYYY-MM-DD",
+                value
+            )
+        })
+}
+
+/// The day a plain `YYYY-MM-DD` value names, `None` for anything else.
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+fn parse_active_enum<T>(label: &str, value: &str) -> Result<T>
+where
+    T: ActiveEnum<Value = String> + Iterable,
+{
+    T::try_from_value(&value.to_lowercase()).map_err(|_| {
+        let supported: Vec<String> = T::iter().map(|v| v.to_value()).collect();
+        anyhow!(
+            "Unsupported {} '{}', expected one of: {}",
+            label,
+            value,
+            supported.join(", ")
+        )
+    })
+}
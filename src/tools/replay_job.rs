@@ -0,0 +1,15 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::{LocalLoreContext, tools::job_status::JobRecord};
+
+#[derive(Debug)]
+pub struct ReplayJobInput {
+    pub job_id: String,
+}
+
+pub async fn replay_job(input: ReplayJobInput, ctx: &Arc<LocalLoreContext>) -> Result<JobRecord> {
+    let record = ctx.application_job_storage.replay(&input.job_id).await?;
+    Ok(JobRecord::from(record))
+}
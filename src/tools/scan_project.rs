@@ -0,0 +1,71 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::{Result, anyhow};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::Serialize;
+
+use crate::{
+    LocalLoreContext,
+    entities::{prelude::Project, project},
+    jobs::{ApplicationJob, submit_application_job},
+};
+
+#[derive(Debug)]
+pub struct ScanProjectInput {
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScanProjectResponse {
+    pub job_id: String,
+    /// The path that is rescanned, a workspace member is rescanned through
+    /// its workspace.
+    pub scanned_path: String,
+}
+
+pub async fn scan_project(
+    input: ScanProjectInput,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<ScanProjectResponse> {
+    let mut candidates = vec![input.path.clone()];
+    if let Some(canonical) = Path::new(&input.path)
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.to_str().map(str::to_string))
+    {
+        candidates.push(canonical);
+    }
+    let project_record = Project::find()
+        .filter(project::Column::Path.is_in(candidates))
+        .one(&ctx.db)
+        .await?
+        .ok_or_else(|| {
+            anyhow!(
+                "No scanned project at {}, use index_project to add it",
+                input.path
+            )
+        })?;
+
+    let scanned_path = match project_record.parent_id {
+        Some(parent_id) => Project::find_by_id(parent_id)
+            .one(&ctx.db)
+            .await?
+            .map(|parent| parent.path)
+            .unwrap_or(project_record.path),
+        None => project_record.path,
+    };
+    if !Path::new(&scanned_path).is_dir() {
+        return Err(anyhow!("{} no longer exists", scanned_path));
+    }
+
+    let job_id = submit_application_job(
+        ApplicationJob::GatherProjectDependencies(scanned_path.clone()),
+        ctx,
+    )
+    .await?;
+
+    Ok(ScanProjectResponse {
+        job_id: job_id.to_string(),
+        scanned_path,
+    })
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use sea_orm::{
+    ColumnTrait, EntityTrait, FromQueryResult, JoinType, QueryFilter, QueryOrder, QuerySelect,
+    RelationTrait, prelude::DateTimeUtc,
+};
+use serde::Serialize;
+
+use crate::{
+    LocalLoreContext,
+    entities::{dependency, prelude::ProjectDependency, project, project_dependency},
+    models::{DependencyKind, ProjectLanguage},
+    tools::{parse_dependency_kind, parse_language},
+};
+
+#[derive(Debug, Default)]
+pub struct SearchDepsInput {
+    pub name: Option<String>,
+    pub language: Option<String>,
+    pub project_path: Option<String>,
+    pub kind: Option<String>,
+    pub direct: Option<bool>,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct DependencyMatch {
+    pub name: String,
+    pub version: String,
+    pub language: ProjectLanguage,
+    pub kind: DependencyKind,
+    pub is_direct: bool,
+    pub project_name: String,
+    pub project_path: String,
+    pub first_seen_at: DateTimeUtc,
+    pub last_seen_at: DateTimeUtc,
+    pub last_indexed_at: Option<DateTimeUtc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchDepsResponse {
+    pub total: usize,
+    pub dependencies: Vec<DependencyMatch>,
+}
+
+pub async fn search_deps(
+    input: SearchDepsInput,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<SearchDepsResponse> {
+    let mut query = ProjectDependency::find()
+        .select_only()
+        .column(dependency::Column::Name)
+        .column(dependency::Column::Version)
+        .column(dependency::Column::Language)
+        .column(project_dependency::Column::Kind)
+        .column(project_dependency::Column::IsDirect)
+        .column_as(project::Column::Name, "project_name")
+        .column_as(project::Column::Path, "project_path")
+        .column(project_dependency::Column::FirstSeenAt)
+        .column(project_dependency::Column::LastSeenAt)
+        .column(dependency::Column::LastIndexedAt)
+        .join(
+            JoinType::InnerJoin,
+            project_dependency::Relation::Dependency.def(),
+        )
+        .join(
+            JoinType::InnerJoin,
+            project_dependency::Relation::Project.def(),
+        );
+
+    if let Some(name) = input.name.filter(|n| !n.is_empty()) {
+        query = query.filter(dependency::Column::Name.contains(name));
+    }
+    if let Some(language) = input.language {
+        query = query.filter(dependency::Column::Language.eq(parse_language(&language)?));
+    }
+    if let Some(kind) = input.kind {
+        query = query.filter(project_dependency::Column::Kind.eq(parse_dependency_kind(&kind)?));
+    }
+    if let Some(direct) = input.direct {
+        query = query.filter(project_dependency::Column::IsDirect.eq(direct));
+    }
+    if let Some(project_path) = input.project_path {
+        query = query.filter(project::Column::Path.eq(project_path));
+    }
+
+    let dependencies = query
+        .order_by_asc(project::Column::Path)
+        .order_by_asc(dependency::Column::Name)
+        .order_by_asc(dependency::Column::Version)
+        .into_model::<DependencyMatch>()
+        .all(&ctx.db)
+        .await?;
+
+    Ok(SearchDepsResponse {
+        total: dependencies.len(),
+        dependencies,
+    })
+}
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use anyhow::{Result, anyhow};
+use sea_orm::{ActiveEnum, DbBackend, FromQueryResult, Statement, Value};
+use serde::Serialize;
+
+use crate::{
+    LocalLoreContext,
+    models::{DocumentKind, ProjectLanguage},
+    tools::parse_language,
+};
+
+const DEFAULT_LIMIT: u64 = 20;
+const MAX_LIMIT: u64 = 100;
+
+#[derive(Debug, Default)]
+pub struct SearchDocsInput {
+    pub query: String,
+    pub project_path: Option<String>,
+    pub language: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, FromQueryResult)]
+pub struct DocumentMatch {
+    pub name: String,
+    pub version: String,
+    pub language: ProjectLanguage,
+    pub kind: DocumentKind,
+    pub item_path: String,
+    pub snippet: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchDocsResponse {
+    pub total: usize,
+    pub results: Vec<DocumentMatch>,
+}
+
+/// Quotes every term so user input is never interpreted as FTS5 query syntax.
+fn to_match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
+pub async fn search_docs(
+    input: SearchDocsInput,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<SearchDocsResponse> {
+    let match_expression =
+        to_match_expression(&input.query).ok_or_else(|| anyhow!("Search query is empty"))?;
+
+    let mut sql = String::from(
+        r#"SELECT dependency.name, dependency.version, dependency.language,
+                dependency_document.kind, dependency_document_chunk.item_path,
+                snippet(dependency_document_chunk, 1, '**', '**', '...', 32) AS snippet,
+                -bm25(dependency_document_chunk) AS score
+            FROM dependency_document_chunk
+            JOIN dependency ON dependency.id = dependency_document_chunk.dependency_id
+            JOIN dependency_document
+                ON dependency_document.id = dependency_document_chunk.document_id
+            WHERE dependency_document_chunk MATCH ?"#,
+    );
+    let mut values: Vec<Value> = vec![match_expression.into()];
+
+    if let Some(language) = input.language {
+        sql.push_str(" AND dependency.language = ?");
+        values.push(parse_language(&language)?.to_value().into());
+    }
+    if let Some(name) = input.name {
+        sql.push_str(" AND dependency.name = ?");
+        values.push(name.into());
+    }
+    if let Some(version) = input.version {
+        sql.push_str(" AND dependency.version = ?");
+        values.push(version.into());
+    }
+    if let Some(project_path) = input.project_path {
+        sql.push_str(
+            r#" AND dependency.id IN (
+                SELECT project_dependency.dependency_id FROM project_dependency
+                JOIN project ON project.id = project_dependency.project_id
+                WHERE project.path = ?)"#,
+        );
+        values.push(project_path.into());
+    }
+
+    let limit = input.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    sql.push_str(" ORDER BY bm25(dependency_document_chunk) LIMIT ?");
+    values.push(limit.into());
+
+    let results = DocumentMatch::find_by_statement(Statement::from_sql_and_values(
+        DbBackend::Sqlite,
+        sql,
+        values,
+    ))
+    .all(&ctx.db)
+    .await?;
+
+    Ok(SearchDocsResponse {
+        total: results.len(),
+        results,
+    })
+}
@@ -0,0 +1,175 @@
+use std::{
+    collections::{HashMap, VecDeque, hash_map::Entry},
+    sync::Arc,
+};
+
+use anyhow::{Result, anyhow};
+use sea_orm::{
+    ColumnTrait, EntityTrait, FromQueryResult, JoinType, QueryFilter, QueryOrder, QuerySelect,
+    RelationTrait,
+};
+use serde::Serialize;
+
+use crate::{
+    LocalLoreContext,
+    entities::{
+        dependency, dependency_edge,
+        prelude::{DependencyEdge, Project, ProjectDependency},
+        project, project_dependency,
+    },
+    models::{DependencyKind, ProjectLanguage},
+    tools::parse_language,
+};
+
+#[derive(Debug)]
+pub struct WhyDependencyInput {
+    pub project_path: String,
+    pub name: String,
+    pub version: Option<String>,
+    pub language: Option<String>,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct ProjectDependencyNode {
+    dependency_id: i32,
+    name: String,
+    version: String,
+    language: ProjectLanguage,
+    kind: DependencyKind,
+    is_direct: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyStep {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyChain {
+    pub name: String,
+    pub version: String,
+    pub language: ProjectLanguage,
+    pub kind: DependencyKind,
+    pub is_direct: bool,
+    /// From the dependency the project declares down to the requested one.
+    /// Empty when the lockfile records no path to it.
+    pub path: Vec<DependencyStep>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WhyDependencyResponse {
+    pub project_name: String,
+    pub project_path: String,
+    pub matches: Vec<DependencyChain>,
+}
+
+pub async fn why_dependency(
+    input: WhyDependencyInput,
+    ctx: &Arc<LocalLoreContext>,
+) -> Result<WhyDependencyResponse> {
+    let language = input.language.as_deref().map(parse_language).transpose()?;
+
+    let project_record = Project::find()
+        .filter(project::Column::Path.eq(&input.project_path))
+        .one(&ctx.db)
+        .await?
+        .ok_or_else(|| anyhow!("No scanned project at {}", input.project_path))?;
+
+    let nodes = ProjectDependency::find()
+        .select_only()
+        .column(project_dependency::Column::DependencyId)
+        .column(dependency::Column::Name)
+        .column(dependency::Column::Version)
+        .column(dependency::Column::Language)
+        .column(project_dependency::Column::Kind)
+        .column(project_dependency::Column::IsDirect)
+        .join(
+            JoinType::InnerJoin,
+            project_dependency::Relation::Dependency.def(),
+        )
+        .filter(project_dependency::Column::ProjectId.eq(project_record.id))
+        .order_by_asc(dependency::Column::Version)
+        .into_model::<ProjectDependencyNode>()
+        .all(&ctx.db)
+        .await?;
+
+    let targets: Vec<&ProjectDependencyNode> = nodes
+        .iter()
+        .filter(|n| n.name == input.name)
+        .filter(|n| input.version.as_ref().is_none_or(|v| &n.version == v))
+        .filter(|n| language.as_ref().is_none_or(|l| &n.language == l))
+        .collect();
+    if targets.is_empty() {
+        return Err(anyhow!(
+            "{} is not a dependency of {}",
+            input.name,
+            input.project_path
+        ));
+    }
+
+    let edges = DependencyEdge::find()
+        .filter(dependency_edge::Column::ProjectId.eq(project_record.id))
+        .all(&ctx.db)
+        .await?;
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for edge in edges {
+        children
+            .entry(edge.parent_id)
+            .or_default()
+            .push(edge.child_id);
+    }
+
+    // Breadth first from every direct dependency at once, so the recorded
+    // parent of each dependency lies on a shortest path from the project.
+    let mut parents: HashMap<i32, Option<i32>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for node in nodes.iter().filter(|n| n.is_direct) {
+        parents.insert(node.dependency_id, None);
+        queue.push_back(node.dependency_id);
+    }
+    while let Some(current) = queue.pop_front() {
+        for &child in children.get(&current).into_iter().flatten() {
+            if let Entry::Vacant(entry) = parents.entry(child) {
+                entry.insert(Some(current));
+                queue.push_back(child);
+            }
+        }
+    }
+
+    let nodes_by_id: HashMap<i32, &ProjectDependencyNode> =
+        nodes.iter().map(|n| (n.dependency_id, n)).collect();
+    let matches = targets
+        .into_iter()
+        .map(|target| {
+            let mut path = Vec::new();
+            let mut current = parents
+                .contains_key(&target.dependency_id)
+                .then_some(target.dependency_id);
+            while let Some(id) = current {
+                if let Some(node) = nodes_by_id.get(&id) {
+                    path.push(DependencyStep {
+                        name: node.name.clone(),
+                        version: node.version.clone(),
+                    });
+                }
+                current = parents.get(&id).copied().flatten();
+            }
+            path.reverse();
+            DependencyChain {
+                name: target.name.clone(),
+                version: target.version.clone(),
+                language: target.language.clone(),
+                kind: target.kind,
+                is_direct: target.is_direct,
+                path,
+            }
+        })
+        .collect();
+
+    Ok(WhyDependencyResponse {
+        project_name: project_record.name,
+        project_path: project_record.path,
+        matches,
+    })
+}
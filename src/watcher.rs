@@ -0,0 +1,232 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use log::debug;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, event::ModifyKind};
+use notify_debouncer_full::{
+    DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache, new_debouncer,
+};
+use sea_orm::EntityTrait;
+use tokio::{sync::mpsc, time::interval};
+
+use crate::{
+    LocalLoreContext,
+    collectors::CollectorRegistry,
+    entities::prelude::Project,
+    jobs::{ApplicationJob, submit_application_job},
+};
+
+/// Changes to a project's files within this window are folded into one
+/// rescan, installs usually rewrite the manifest and the lockfile together.
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(2);
+/// How often the project table is checked for projects to start watching.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Watches the directory of every known project and enqueues a rescan when a
+/// file one of the collectors reads changes.
+pub async fn watch_projects(ctx: Arc<LocalLoreContext>) -> Result<()> {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let mut debouncer = new_debouncer(
+        DEBOUNCE_TIMEOUT,
+        None,
+        move |result: DebounceEventResult| {
+            let _ = sender.send(result);
+        },
+    )?;
+
+    // Workspace members are rescanned through their workspace, so each
+    // watched directory maps to the project path that is scanned.
+    let mut watched: HashMap<PathBuf, String> = HashMap::new();
+    let mut refresh = interval(REFRESH_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = refresh.tick() => {
+                let refreshed = refresh_watched_projects(&ctx, &mut debouncer, &mut watched).await;
+                if let Err(e) = refreshed {
+                    debug!("Failed to refresh watched projects: {}", e);
+                }
+            }
+            Some(result) = receiver.recv() => {
+                let events = match result {
+                    Ok(events) => events,
+                    Err(errors) => {
+                        debug!("File watcher errors: {:?}", errors);
+                        continue;
+                    }
+                };
+                for path in rescanned_projects(&events, &watched, &ctx.collectors) {
+                    let job = ApplicationJob::GatherProjectDependencies(path.clone());
+                    match ctx.application_job_storage.is_pending(&job).await {
+                        Ok(true) => {
+                            debug!("Dependency files changed, a rescan of {} is already pending", path);
+                            continue;
+                        }
+                        Ok(false) => {}
+                        Err(e) => debug!("Failed to look up pending rescans of {}: {}", path, e),
+                    }
+                    debug!("Dependency files changed, rescanning {}", path);
+                    if let Err(e) = submit_application_job(job, &ctx).await {
+                        debug!("Failed to enqueue rescan of {}: {}", path, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The projects to rescan for a batch of debounced events, each once however
+/// many of its files changed. Collectors reading the files show up as access
+/// events, only changes to their contents matter.
+fn rescanned_projects<'a>(
+    events: &[DebouncedEvent],
+    watched: &'a HashMap<PathBuf, String>,
+    collectors: &CollectorRegistry,
+) -> HashSet<&'a String> {
+    events
+        .iter()
+        .filter(|event| match event.kind {
+            EventKind::Create(_) | EventKind::Remove(_) => true,
+            EventKind::Modify(kind) => !matches!(kind, ModifyKind::Metadata(_)),
+            _ => false,
+        })
+        .flat_map(|event| &event.paths)
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| collectors.watches(n))
+        })
+        .filter_map(|path| watched.get(path.parent()?))
+        .collect()
+}
+
+async fn refresh_watched_projects(
+    ctx: &Arc<LocalLoreContext>,
+    debouncer: &mut Debouncer<RecommendedWatcher, RecommendedCache>,
+    watched: &mut HashMap<PathBuf, String>,
+) -> Result<()> {
+    let projects = Project::find().all(&ctx.db).await?;
+    let paths: HashMap<i32, &str> = projects.iter().map(|p| (p.id, p.path.as_str())).collect();
+
+    watched.retain(|dir, _| {
+        let exists = dir.is_dir();
+        if !exists {
+            debug!("Project directory {} is gone, unwatching", dir.display());
+            let _ = debouncer.unwatch(dir);
+        }
+        exists
+    });
+
+    for project in &projects {
+        let scanned_path = project
+            .parent_id
+            .and_then(|id| paths.get(&id).copied())
+            .unwrap_or(&project.path);
+        watch_directory(
+            debouncer,
+            watched,
+            PathBuf::from(&project.path),
+            scanned_path,
+        );
+
+        // Extra paths from the config are scanned as part of the project.
+        if let Some(project_config) = ctx.config.project(&project.path) {
+            for dir in project_config.extra_dirs() {
+                watch_directory(debouncer, watched, dir, &project.path);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn watch_directory(
+    debouncer: &mut Debouncer<RecommendedWatcher, RecommendedCache>,
+    watched: &mut HashMap<PathBuf, String>,
+    dir: PathBuf,
+    scanned_path: &str,
+) {
+    if watched.contains_key(&dir) || !dir.is_dir() {
+        return;
+    }
+    match debouncer.watch(&dir, RecursiveMode::NonRecursive) {
+        Ok(()) => {
+            debug!("Watching {} for dependency changes", dir.display());
+            watched.insert(dir, scanned_path.to_string());
+        }
+        Err(e) => debug!("Failed to watch {}: {}", dir.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use notify::{
+        Event,
+        event::{AccessKind, CreateKind, DataChange, MetadataKind, RemoveKind},
+    };
+
+    use super::*;
+
+    fn event(kind: EventKind, path: &str) -> DebouncedEvent {
+        DebouncedEvent::new(
+            Event::new(kind).add_path(PathBuf::from(path)),
+            Instant::now(),
+        )
+    }
+
+    fn rescans(events: &[DebouncedEvent]) -> Vec<String> {
+        let watched = HashMap::from([
+            (PathBuf::from("/code/app"), "/code/app".to_string()),
+            // A workspace member is rescanned through its workspace.
+            (PathBuf::from("/code/app/web"), "/code/app".to_string()),
+            (PathBuf::from("/code/lib"), "/code/lib".to_string()),
+        ]);
+        let mut paths: Vec<String> =
+            rescanned_projects(events, &watched, &CollectorRegistry::builtin())
+                .into_iter()
+                .cloned()
+                .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn rescans_each_changed_project_once() {
+        let modify = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        let events = [
+            event(modify, "/code/app/Cargo.toml"),
+            event(modify, "/code/app/Cargo.lock"),
+            event(
+                EventKind::Create(CreateKind::File),
+                "/code/app/web/package.json",
+            ),
+            event(EventKind::Remove(RemoveKind::File), "/code/lib/go.mod"),
+        ];
+        assert_eq!(rescans(&events), ["/code/app", "/code/lib"]);
+    }
+
+    #[test]
+    fn ignores_events_that_change_no_dependency_file() {
+        let modify = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        let events = [
+            // Collectors reading the files.
+            event(EventKind::Access(AccessKind::Read), "/code/app/Cargo.lock"),
+            event(
+                EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime)),
+                "/code/app/Cargo.lock",
+            ),
+            // Files no collector reads.
+            event(modify, "/code/app/README.md"),
+            event(modify, "/code/app/src/Cargo.toml"),
+            // Directories that are not watched.
+            event(modify, "/code/other/Cargo.lock"),
+        ];
+        assert!(rescans(&events).is_empty());
+    }
+}
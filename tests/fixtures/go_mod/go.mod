@@ -0,0 +1,22 @@
+module example.com/app
+
+go 1.21
+
+require github.com/pkg/errors v0.9.1
+
+require (
+	"github.com/quoted/mod" v1.0.0
+	github.com/stretchr/testify v1.8.4
+	golang.org/x/text v0.14.0 // indirect
+)
+
+require github.com/davecgh/go-spew v1.1.1 // indirect
+
+replace github.com/pkg/errors => github.com/fork/errors v0.9.2
+
+replace (
+	github.com/local/tool => ../tool
+	github.com/old/lib => github.com/new/lib v2.0.0+incompatible
+	golang.org/x/crypto v0.21.0 => golang.org/x/crypto v0.17.0
+	golang.org/x/net v0.10.0 => golang.org/x/net v0.17.0
+)